@@ -1,29 +1,181 @@
 use syn::{
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
-    Error, LitStr, MetaList, Result, Token,
+    Error, Expr, ExprLit, Ident, Lit, LitStr, Meta, Result, Token,
 };
 
+use crate::{duration::parse_duration, suggest::did_you_mean};
+
+const KEYS: &[&str] = &["aliases", "cooldown", "group", "name", "permission"];
+
 pub struct CommandAttrs {
     pub aliases: Punctuated<LitStr, Token![,]>,
+    /// Name of the group if this is a subcommand
+    pub group: Option<LitStr>,
+    /// Name that overrides the function name
+    pub name: Option<LitStr>,
+    /// Cooldown in milliseconds
+    pub cooldown: Option<u64>,
+    pub permission: Option<Permission>,
+}
+
+pub enum Permission {
+    Everyone,
+    Moderator,
+    Admin,
+    Owner,
+    Role(String),
+}
+
+impl Permission {
+    const NAMES: &'static [&'static str] = &["everyone", "mod", "moderator", "admin", "owner"];
+
+    fn parse(expr: &Expr) -> Result<Self> {
+        let Some(Lit::Str(lit)) = expr_lit(expr) else {
+            return Err(Error::new_spanned(expr, "expected a string literal"));
+        };
+
+        let permission = match lit.value().as_str() {
+            "everyone" => Self::Everyone,
+            "mod" | "moderator" => Self::Moderator,
+            "admin" => Self::Admin,
+            "owner" => Self::Owner,
+            value => match value.strip_prefix("role:") {
+                Some(role) if !role.is_empty() => Self::Role(role.to_owned()),
+                Some(_) => {
+                    let content = "missing role name, expected a custom role like `\"role:name\"`";
+
+                    return Err(Error::new_spanned(lit, content));
+                }
+                None => {
+                    let content = match did_you_mean(value, Self::NAMES) {
+                        Some(name) => format!("unknown permission, did you mean `\"{name}\"`?"),
+                        None => "expected `\"everyone\"`, `\"mod\"`, `\"admin\"`, \
+                            `\"owner\"`, or a custom role like `\"role:name\"`"
+                            .to_owned(),
+                    };
+
+                    return Err(Error::new_spanned(lit, content));
+                }
+            },
+        };
+
+        Ok(permission)
+    }
 }
 
 impl Parse for CommandAttrs {
     fn parse(input: ParseStream) -> Result<Self> {
-        if input.is_empty() {
-            return Ok(Self {
-                aliases: Punctuated::new(),
-            });
+        let mut aliases = Punctuated::new();
+        let mut group = None;
+        let mut name = None;
+        let mut cooldown = None;
+        let mut permission = None;
+
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        let mut seen = Vec::new();
+
+        for meta in metas {
+            let key = meta.path().get_ident().map(Ident::to_string);
+
+            if let Some(key) = key.as_ref().filter(|key| seen.contains(*key)) {
+                let content = format!("duplicate `{key}` attribute");
+
+                return Err(Error::new_spanned(meta.path(), content));
+            }
+
+            match (key.as_deref(), &meta) {
+                (Some("aliases"), Meta::List(list)) => {
+                    aliases = list.parse_args_with(Punctuated::parse_separated_nonempty)?;
+                    validate_aliases(&aliases)?;
+                }
+                (Some("cooldown"), Meta::NameValue(name_value)) => {
+                    cooldown = Some(parse_duration(&name_value.value)?);
+                }
+                (Some("permission"), Meta::NameValue(name_value)) => {
+                    permission = Some(Permission::parse(&name_value.value)?);
+                }
+                (Some("group"), Meta::NameValue(name_value)) => {
+                    group = Some(parse_word(&name_value.value)?);
+                }
+                (Some("name"), Meta::NameValue(name_value)) => {
+                    name = Some(parse_word(&name_value.value)?);
+                }
+                (Some("aliases"), _) => {
+                    return Err(Error::new_spanned(meta, "expected `aliases(\"...\", ...)`"));
+                }
+                (Some(key @ ("cooldown" | "group" | "name" | "permission")), _) => {
+                    let content = format!("expected `{key} = \"...\"`");
+
+                    return Err(Error::new_spanned(meta, content));
+                }
+                _ => {
+                    let suggestion = key.as_deref().and_then(|key| did_you_mean(key, KEYS));
+
+                    let content = match suggestion {
+                        Some(key) => format!("unknown attribute, did you mean `{key}`?"),
+                        None => "expected `aliases(...)`, `cooldown = \"...\"`, \
+                            `group = \"...\"`, `name = \"...\"`, or `permission = \"...\"`"
+                            .to_owned(),
+                    };
+
+                    return Err(Error::new_spanned(meta.path(), content));
+                }
+            }
+
+            seen.extend(key);
         }
 
-        let meta = input.parse::<MetaList>()?;
+        Ok(Self {
+            aliases,
+            group,
+            name,
+            cooldown,
+            permission,
+        })
+    }
+}
+
+fn parse_word(expr: &Expr) -> Result<LitStr> {
+    let Some(Lit::Str(lit)) = expr_lit(expr) else {
+        return Err(Error::new_spanned(expr, "expected a string literal"));
+    };
 
-        let aliases = if meta.path.is_ident("aliases") {
-            meta.parse_args_with(Punctuated::parse_separated_nonempty)?
-        } else {
-            return Err(Error::new_spanned(meta.path, "expected `aliases`"));
-        };
+    if !is_word(&lit.value()) {
+        return Err(Error::new_spanned(lit, "names must be a single word"));
+    }
+
+    Ok(lit.to_owned())
+}
+
+fn is_word(value: &str) -> bool {
+    !(value.is_empty() || value.contains(char::is_whitespace))
+}
+
+fn validate_aliases(aliases: &Punctuated<LitStr, Token![,]>) -> Result<()> {
+    for (i, alias) in aliases.iter().enumerate() {
+        let value = alias.value();
+
+        if !is_word(&value) {
+            return Err(Error::new_spanned(alias, "aliases must be a single word"));
+        }
+
+        if aliases
+            .iter()
+            .take(i)
+            .any(|prev| prev.value().eq_ignore_ascii_case(&value))
+        {
+            return Err(Error::new_spanned(alias, "duplicate alias"));
+        }
+    }
+
+    Ok(())
+}
 
-        Ok(Self { aliases })
+/// Unwrap the literal of an attribute value like `name = "value"`.
+pub fn expr_lit(expr: &Expr) -> Option<&Lit> {
+    match expr {
+        Expr::Lit(ExprLit { lit, .. }) => Some(lit),
+        _ => None,
     }
 }
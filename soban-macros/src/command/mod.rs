@@ -2,15 +2,25 @@ pub mod attrs;
 pub mod model;
 
 use proc_macro2::TokenStream;
-use quote::{format_ident, quote};
-use syn::Result;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{Attribute, Error, Lit, LitStr, Meta, Result};
 
-use self::{attrs::CommandAttrs, model::command::CommandFn};
+use self::{
+    attrs::{expr_lit, CommandAttrs, Permission},
+    model::command::CommandFn,
+};
 
 pub fn impl_command(cmd_attrs: CommandAttrs, cmd_fn: CommandFn) -> Result<TokenStream> {
-    let CommandAttrs { aliases } = cmd_attrs;
+    let CommandAttrs {
+        aliases,
+        group,
+        name,
+        cooldown,
+        permission,
+    } = cmd_attrs;
 
     let CommandFn {
+        attrs,
         vis,
         async_token,
         fn_token,
@@ -26,32 +36,118 @@ pub fn impl_command(cmd_attrs: CommandAttrs, cmd_fn: CommandFn) -> Result<TokenS
     let orig_name = &run_args.orig.name;
     let args_name = &run_args.args.name;
 
-    let ret_ty = &ret.ty;
+    let fn_name = cmd_ident.to_string();
+    let cmd_name = name.as_ref().map_or_else(|| fn_name.clone(), LitStr::value);
 
-    let cmd_name = cmd_ident.to_string();
+    if let Some(alias) = aliases
+        .iter()
+        .find(|alias| alias.value().eq_ignore_ascii_case(&cmd_name))
+    {
+        return Err(Error::new_spanned(
+            alias,
+            "alias is the same as the command name",
+        ));
+    }
 
-    let static_name = format_ident!("{}", cmd_name.to_uppercase(), span = cmd_ident.span());
+    let (group, qualified_name) = match group {
+        Some(group) => {
+            let qualified_name = format!("{} {cmd_name}", group.value());
 
-    let run_fn_name = format_ident!("run_{cmd_name}");
+            (quote!(Some(#group)), qualified_name)
+        }
+        None => (quote!(None), cmd_name.clone()),
+    };
+
+    let description = description(&attrs);
+
+    // points at the name so that conflicting commands can be reported with their definition
+    let location = quote_spanned!(cmd_ident.span()=> concat!(file!(), ":", line!()));
+
+    let static_name = format_ident!("{}", fn_name.to_uppercase(), span = cmd_ident.span());
+
+    let run_fn_name = format_ident!("run_{fn_name}");
 
     let cmd_slice_path = quote!(crate::COMMANDS_SLICE);
     let cmd_path = quote!(crate::Command);
     let box_fut_path = quote!(::futures::future::BoxFuture);
+    // `Result<()>` is turned into an empty reply
+    let result_path = quote!(::eyre::Result<crate::Reply>);
+
+    let cooldown = match cooldown {
+        Some(millis) => quote!(Some(::std::time::Duration::from_millis(#millis))),
+        None => quote!(None),
+    };
+
+    let permission_path = quote!(crate::Permission);
+
+    let permission = match permission.unwrap_or(Permission::Everyone) {
+        Permission::Everyone => quote!(#permission_path::Everyone),
+        Permission::Moderator => quote!(#permission_path::Moderator),
+        Permission::Admin => quote!(#permission_path::Admin),
+        Permission::Owner => quote!(#permission_path::Owner),
+        Permission::Role(role) => quote!(#permission_path::Role(#role)),
+    };
 
     let tokens = quote! {
         #[linkme::distributed_slice( #cmd_slice_path )]
         pub static #static_name: #cmd_path = #cmd_path {
             name: #cmd_name,
+            group: #group,
+            qualified_name: #qualified_name,
+            description: #description,
             aliases: &[ #aliases ],
+            cooldown: #cooldown,
+            permission: #permission,
+            location: #location,
             run: #run_fn_name,
         };
 
-        fn #run_fn_name<'fut>( #run_args ) -> #box_fut_path<'fut, #ret_ty> {
-            Box::pin( #cmd_ident( #ctx_name, #orig_name, #args_name ) )
+        fn #run_fn_name<'fut>( #run_args ) -> #box_fut_path<'fut, #result_path> {
+            Box::pin(async move {
+                #cmd_ident( #ctx_name, #orig_name, #args_name )
+                    .await
+                    .map(::core::convert::Into::into)
+            })
         }
 
+        #(#attrs)*
         #vis #async_token #fn_token #cmd_ident <'fut> ( #cmd_args ) #ret #body
     };
 
     Ok(tokens)
 }
+
+/// First paragraph of the doc comments, joined into a single line.
+fn description(attrs: &[Attribute]) -> String {
+    let lines = attrs.iter().filter_map(|attr| match &attr.meta {
+        Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+            match expr_lit(&name_value.value) {
+                Some(Lit::Str(lit)) => Some(lit.value()),
+                _ => None,
+            }
+        }
+        _ => None,
+    });
+
+    let mut description = String::new();
+
+    for line in lines {
+        let line = line.trim();
+
+        if line.is_empty() {
+            if description.is_empty() {
+                continue;
+            }
+
+            break;
+        }
+
+        if !description.is_empty() {
+            description.push(' ');
+        }
+
+        description.push_str(line);
+    }
+
+    description
+}
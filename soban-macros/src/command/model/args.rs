@@ -3,10 +3,15 @@ use quote::{format_ident, quote, ToTokens};
 use syn::{
     parenthesized,
     parse::{Parse, ParseStream},
-    parse_quote,
+    punctuated::Punctuated,
     spanned::Spanned,
     token::{Mut, Underscore},
-    Error, Ident, Result, Token, Type,
+    Error, GenericArgument, Ident, Result, Token, Type,
+};
+
+use super::{
+    ty::{angle_args, args_of, has_elided_lifetime, is_plain, set_fut_lifetime},
+    FnKind,
 };
 
 #[derive(Clone)]
@@ -16,33 +21,47 @@ pub struct Args {
     pub args: Arg,
 }
 
-impl Args {
-    pub fn validate(&mut self) -> Result<()> {
-        if &*self.ctx.ty != &parse_quote!(Arc<Context>) {
-            let content = "first argument must have type `Arc<Context>`";
+/// Arguments of the function that an attribute is applied to.
+pub trait FnArgs: Sized {
+    fn parse_kind(input: ParseStream, kind: FnKind) -> Result<Self>;
 
-            return Err(Error::new_spanned(&self.ctx, content));
-        }
+    fn validate(&mut self, kind: FnKind) -> Result<()>;
+}
 
-        if &*self.orig.ty != &parse_quote!(CommandOrigin<'_>) {
-            let content = "second argument must have type `CommandOrigin<'_>`";
+impl FnArgs for Args {
+    fn validate(&mut self, kind: FnKind) -> Result<()> {
+        validate_ctx(&self.ctx.ty)?;
+        validate_lifetime_arg(&mut self.orig.ty, "CommandOrigin", "second")?;
+        validate_lifetime_arg(&mut self.args.ty, kind.last_arg(), "third")?;
 
-            return Err(Error::new_spanned(&self.orig, content));
-        }
+        Ok(())
+    }
 
-        self.orig.ty = parse_quote!(CommandOrigin<'fut>);
+    fn parse_kind(input: ParseStream, kind: FnKind) -> Result<Self> {
+        let content;
+        let paren = parenthesized!(content in input);
 
-        if &*self.args.ty != &parse_quote!(Args<'_>) {
-            let content = "third argument must have type `Args<'_>`";
+        let mut list = Punctuated::<Arg, Token![,]>::parse_terminated(&content)?.into_iter();
+        let plural = kind.plural();
+        let signature = kind.signature();
 
-            return Err(Error::new_spanned(&self.args, content));
-        }
+        match (list.next(), list.next(), list.next(), list.next()) {
+            (Some(ctx), Some(orig), Some(args), None) => Ok(Self { ctx, orig, args }),
+            (.., Some(extra)) => {
+                let content = format!("too many arguments, {plural} take {signature}");
 
-        self.args.ty = parse_quote!(Args<'fut>);
+                Err(Error::new_spanned(extra, content))
+            }
+            _ => {
+                let content = format!("missing arguments, {plural} take {signature}");
 
-        Ok(())
+                Err(Error::new(paren.span.join(), content))
+            }
+        }
     }
+}
 
+impl Args {
     pub fn ensure_names(&mut self) {
         let ctx_name = format_ident!("ctx", span = self.ctx.name.span());
         self.ctx.name = ArgName::Ident(ctx_name);
@@ -55,28 +74,76 @@ impl Args {
     }
 }
 
-impl Parse for Args {
-    fn parse(input: ParseStream) -> Result<Self> {
+impl ToTokens for Args {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.ctx.to_tokens(tokens);
+        Token![,](Span::call_site()).to_tokens(tokens);
+        self.orig.to_tokens(tokens);
+        Token![,](Span::call_site()).to_tokens(tokens);
+        self.args.to_tokens(tokens);
+    }
+}
+
+/// Arguments of a task, which run without an origin.
+#[derive(Clone)]
+pub struct TaskArgs {
+    pub ctx: Arg,
+    pub channels: Arg,
+}
+
+impl FnArgs for TaskArgs {
+    fn parse_kind(input: ParseStream, kind: FnKind) -> Result<Self> {
         let content;
-        parenthesized!(content in input);
+        let paren = parenthesized!(content in input);
+
+        let mut list = Punctuated::<Arg, Token![,]>::parse_terminated(&content)?.into_iter();
+        let signature = kind.signature();
 
-        let ctx = content.parse::<Arg>()?;
-        let _ = content.parse::<Token![,]>()?;
-        let orig = content.parse::<Arg>()?;
-        let _ = content.parse::<Token![,]>()?;
-        let args = content.parse::<Arg>()?;
+        match (list.next(), list.next(), list.next()) {
+            (Some(ctx), Some(channels), None) => Ok(Self { ctx, channels }),
+            (.., Some(extra)) => {
+                let content = format!("too many arguments, tasks take {signature}");
 
-        Ok(Self { ctx, orig, args })
+                Err(Error::new_spanned(extra, content))
+            }
+            _ => {
+                let content = format!("missing arguments, tasks take {signature}");
+
+                Err(Error::new(paren.span.join(), content))
+            }
+        }
+    }
+
+    fn validate(&mut self, kind: FnKind) -> Result<()> {
+        validate_ctx(&self.ctx.ty)?;
+
+        let name = kind.last_arg();
+
+        if !is_plain(&self.channels.ty, name) {
+            let content = format!("second argument must have type `{name}`");
+
+            return Err(Error::new_spanned(&self.channels.ty, content));
+        }
+
+        Ok(())
     }
 }
 
-impl ToTokens for Args {
+impl TaskArgs {
+    pub fn ensure_names(&mut self) {
+        let ctx_name = format_ident!("ctx", span = self.ctx.name.span());
+        self.ctx.name = ArgName::Ident(ctx_name);
+
+        let channels_name = format_ident!("channels", span = self.channels.name.span());
+        self.channels.name = ArgName::Ident(channels_name);
+    }
+}
+
+impl ToTokens for TaskArgs {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.ctx.to_tokens(tokens);
         Token![,](Span::call_site()).to_tokens(tokens);
-        self.orig.to_tokens(tokens);
-        Token![,](Span::call_site()).to_tokens(tokens);
-        self.args.to_tokens(tokens);
+        self.channels.to_tokens(tokens);
     }
 }
 
@@ -88,6 +155,67 @@ pub struct Arg {
     pub ty: Box<Type>,
 }
 
+fn validate_ctx(ty: &Type) -> Result<()> {
+    let is_arc_context =
+        args_of(ty, "Arc")
+            .map(angle_args)
+            .is_some_and(|args| match args.as_slice() {
+                [GenericArgument::Type(inner)] => is_plain(inner, "Context"),
+                _ => false,
+            });
+
+    if is_arc_context {
+        return Ok(());
+    }
+
+    let is_context = match ty {
+        Type::Reference(reference) => is_plain(&reference.elem, "Context"),
+        _ => is_plain(ty, "Context"),
+    };
+
+    let content = if is_context {
+        "first argument must have type `Arc<Context>`, wrap the context in an `Arc`"
+    } else {
+        "first argument must have type `Arc<Context>`"
+    };
+
+    Err(Error::new_spanned(ty, content))
+}
+
+/// Ensure the type is `name<'_>` and replace its lifetime with `'fut`.
+fn validate_lifetime_arg(ty: &mut Type, name: &str, position: &str) -> Result<()> {
+    if has_elided_lifetime(ty, name) {
+        set_fut_lifetime(ty);
+
+        return Ok(());
+    }
+
+    let expected = format!("{position} argument must have type `{name}<'_>`");
+
+    if let Some(args) = args_of(ty, name) {
+        if let [GenericArgument::Lifetime(lifetime)] = angle_args(args).as_slice() {
+            let content = format!("{expected}, use the anonymous lifetime `'_`");
+
+            return Err(Error::new_spanned(lifetime, content));
+        }
+
+        return Err(Error::new_spanned(args, expected));
+    }
+
+    let is_str = match ty {
+        Type::Reference(reference) => is_plain(&reference.elem, "str"),
+        _ => false,
+    };
+
+    let content = if name == "Args" && is_str {
+        format!("{expected}, the message is available as `args.msg`")
+    } else {
+        expected
+    };
+
+    Err(Error::new_spanned(ty, content))
+}
+
 impl Parse for Arg {
     fn parse(input: ParseStream) -> Result<Self> {
         let mutability = input.peek(Token![mut]).then(|| input.parse()).transpose()?;
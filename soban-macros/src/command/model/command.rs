@@ -1,27 +1,59 @@
 use syn::{
-    parse::{Parse, ParseStream},
+    parse::ParseStream,
     token::{Async, Fn},
-    Block, Ident, Result, Token, Visibility,
+    Attribute, Block, Error, Generics, Ident, Result, Token, Visibility,
 };
 
-use crate::command::model::{args::Args, ret::ReturnResult};
+use crate::command::model::{
+    args::{Args, FnArgs, TaskArgs},
+    ret::ReturnResult,
+    FnKind,
+};
 
-pub struct CommandFn {
+pub struct CommandFn<A = Args> {
+    pub attrs: Vec<Attribute>,
     pub vis: Visibility,
     pub async_token: Async,
     pub fn_token: Fn,
     pub name: Ident,
-    pub args: Args,
+    pub args: A,
     pub ret: ReturnResult,
     pub body: Block,
 }
 
-impl Parse for CommandFn {
-    fn parse(input: ParseStream) -> Result<Self> {
+impl CommandFn {
+    pub fn parse_command(input: ParseStream) -> Result<Self> {
+        Self::parse_kind(input, FnKind::Command)
+    }
+
+    pub fn parse_listener(input: ParseStream) -> Result<Self> {
+        Self::parse_kind(input, FnKind::Listener)
+    }
+}
+
+impl CommandFn<TaskArgs> {
+    pub fn parse_task(input: ParseStream) -> Result<Self> {
+        Self::parse_kind(input, FnKind::Task)
+    }
+}
+
+impl<A: FnArgs> CommandFn<A> {
+    fn parse_kind(input: ParseStream, kind: FnKind) -> Result<Self> {
+        let plural = kind.plural();
+
+        // #[...] / /// ...
+        let attrs = input.call(Attribute::parse_outer)?;
+
         // pub / nothing
         let vis = input.parse::<Visibility>()?;
 
         // async
+        if input.peek(Token![fn]) {
+            let content = format!("{plural} must be `async`, add `async` before `fn`");
+
+            return Err(input.error(content));
+        }
+
         let async_token = input.parse::<Token![async]>()?;
 
         // fn
@@ -30,18 +62,26 @@ impl Parse for CommandFn {
         // name
         let name = input.parse::<Ident>()?;
 
-        // (Arc<Context>, CommandOrigin<'_>, &str)
-        let mut args = input.parse::<Args>()?;
-        args.validate()?;
+        if input.peek(Token![<]) {
+            let generics = input.parse::<Generics>()?;
+            let content = format!("{plural} can't have generic parameters");
+
+            return Err(Error::new_spanned(generics, content));
+        }
+
+        // (Arc<Context>, CommandOrigin<'_>, Args<'_>) / (Arc<Context>, Channels)
+        let mut args = A::parse_kind(input, kind)?;
+        args.validate(kind)?;
 
         // -> Result<()>
-        let mut ret = input.parse::<ReturnResult>()?;
-        ret.validate()?;
+        let ret = ReturnResult::parse_kind(input, kind)?;
+        ret.validate(kind)?;
 
         // { ... }
         let body = input.parse::<Block>()?;
 
         Ok(Self {
+            attrs,
             vis,
             async_token,
             fn_token,
@@ -1,3 +1,57 @@
 pub mod args;
 pub mod command;
 pub mod ret;
+pub mod ty;
+
+/// Which attribute a function is parsed for.
+#[derive(Copy, Clone)]
+pub enum FnKind {
+    Command,
+    Listener,
+    Task,
+}
+
+impl FnKind {
+    /// Plural of the kind as used in error messages
+    pub fn plural(self) -> &'static str {
+        match self {
+            Self::Command => "commands",
+            Self::Listener => "listeners",
+            Self::Task => "tasks",
+        }
+    }
+
+    /// Type name of the last argument
+    pub fn last_arg(self) -> &'static str {
+        match self {
+            Self::Command => "Args",
+            Self::Listener => "Event",
+            Self::Task => "Channels",
+        }
+    }
+
+    pub fn signature(self) -> &'static str {
+        match self {
+            Self::Command => "`(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>)`",
+            Self::Listener => "`(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>)`",
+            Self::Task => "`(ctx: Arc<Context>, channels: Channels)`",
+        }
+    }
+
+    /// Accepted return types as used in error messages
+    pub fn return_type(self) -> &'static str {
+        match self {
+            Self::Command => "`Result<Reply>` or `Result<()>`",
+            Self::Listener | Self::Task => "`Result<()>`",
+        }
+    }
+
+    /// Where the output of the function goes, used to explain the return type
+    pub fn output(self) -> &'static str {
+        match self {
+            Self::Command => "the reply is sent by the dispatcher",
+            Self::Listener => "replies are sent through the origin",
+            Self::Task => "messages are posted through the channels",
+        }
+    }
+}
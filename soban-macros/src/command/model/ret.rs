@@ -1,10 +1,10 @@
 use proc_macro2::TokenStream;
 use quote::ToTokens;
-use syn::{
-    parse::{Parse, ParseStream},
-    parse_quote,
-    token::RArrow,
-    Error, Result, Token, Type,
+use syn::{parse::ParseStream, token::RArrow, Error, GenericArgument, Result, Token, Type};
+
+use super::{
+    ty::{angle_args, args_of, is_plain, is_unit},
+    FnKind,
 };
 
 #[derive(Clone)]
@@ -14,19 +14,47 @@ pub struct ReturnResult {
 }
 
 impl ReturnResult {
-    pub fn validate(&mut self) -> Result<()> {
-        let expected_ty = parse_quote!(Result<()>);
+    pub fn validate(&self, kind: FnKind) -> Result<()> {
+        let expected = kind.return_type();
+
+        let Some(args) = args_of(&self.ty, "Result") else {
+            return Err(Error::new_spanned(&self.ty, format!("expected {expected}")));
+        };
+
+        // only commands may return a reply for the dispatcher to send
+        let is_ok =
+            |ty: &Type| is_unit(ty) || matches!(kind, FnKind::Command) && is_plain(ty, "Reply");
+
+        let is_valid = match angle_args(args).as_slice() {
+            [GenericArgument::Type(ok)] => is_ok(ok),
+            [GenericArgument::Type(ok), GenericArgument::Type(err)] => {
+                is_ok(ok) && is_plain(err, "Report")
+            }
+            _ => false,
+        };
+
+        if !is_valid {
+            let content = format!("expected {expected}, {}", kind.output());
 
-        if &*self.ty != &expected_ty {
-            return Err(Error::new_spanned(&self.ty, "expected `Result<()>`"));
+            return Err(Error::new_spanned(args, content));
         }
 
         Ok(())
     }
 }
 
-impl Parse for ReturnResult {
-    fn parse(input: ParseStream) -> Result<Self> {
+impl ReturnResult {
+    pub fn parse_kind(input: ParseStream, kind: FnKind) -> Result<Self> {
+        if !input.peek(Token![->]) {
+            let content = format!(
+                "missing return type, {} must return {}",
+                kind.plural(),
+                kind.return_type()
+            );
+
+            return Err(input.error(content));
+        }
+
         let arrow = input.parse::<Token![->]>()?;
         let ty = input.parse::<Box<Type>>()?;
 
@@ -0,0 +1,75 @@
+use syn::{
+    parse_quote, AngleBracketedGenericArguments, GenericArgument, PathArguments, PathSegment, Type,
+};
+
+/// Last segment of a plain path type so that `std::sync::Arc<Context>` and
+/// `Arc<Context>` are treated the same.
+pub fn last_segment(ty: &Type) -> Option<&PathSegment> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last(),
+        Type::Group(group) => last_segment(&group.elem),
+        Type::Paren(paren) => last_segment(&paren.elem),
+        _ => None,
+    }
+}
+
+fn last_segment_mut(ty: &mut Type) -> Option<&mut PathSegment> {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.segments.last_mut(),
+        Type::Group(group) => last_segment_mut(&mut group.elem),
+        Type::Paren(paren) => last_segment_mut(&mut paren.elem),
+        _ => None,
+    }
+}
+
+/// Generic arguments of the type if its last segment is named `name`.
+pub fn args_of<'a>(ty: &'a Type, name: &str) -> Option<&'a PathArguments> {
+    last_segment(ty)
+        .filter(|segment| segment.ident == name)
+        .map(|segment| &segment.arguments)
+}
+
+/// Generic arguments in angle brackets, e.g. `Context` in `Arc<Context>`.
+pub fn angle_args(args: &PathArguments) -> Vec<&GenericArgument> {
+    match args {
+        PathArguments::AngleBracketed(AngleBracketedGenericArguments { args, .. }) => {
+            args.iter().collect()
+        }
+        PathArguments::None | PathArguments::Parenthesized(_) => Vec::new(),
+    }
+}
+
+/// Whether the type is named `name` and has no generic arguments.
+pub fn is_plain(ty: &Type, name: &str) -> bool {
+    matches!(args_of(ty, name), Some(PathArguments::None))
+}
+
+/// Whether the type is named `name` and its only generic argument is either
+/// `'_` or elided entirely.
+pub fn has_elided_lifetime(ty: &Type, name: &str) -> bool {
+    let Some(args) = args_of(ty, name) else {
+        return false;
+    };
+
+    match angle_args(args).as_slice() {
+        [] => matches!(args, PathArguments::None),
+        [GenericArgument::Lifetime(lifetime)] => lifetime.ident == "_",
+        _ => false,
+    }
+}
+
+/// Replace the generic arguments of the last segment with `<'fut>`.
+pub fn set_fut_lifetime(ty: &mut Type) {
+    if let Some(segment) = last_segment_mut(ty) {
+        segment.arguments = PathArguments::AngleBracketed(parse_quote!(<'fut>));
+    }
+}
+
+pub fn is_unit(ty: &Type) -> bool {
+    match ty {
+        Type::Tuple(tuple) => tuple.elems.is_empty(),
+        Type::Group(group) => is_unit(&group.elem),
+        Type::Paren(paren) => is_unit(&paren.elem),
+        _ => false,
+    }
+}
@@ -0,0 +1,83 @@
+use syn::{Error, Expr, Lit, Result};
+
+use crate::{command::attrs::expr_lit, suggest::did_you_mean};
+
+/// Parse a duration attribute value into milliseconds.
+///
+/// Accepts either an integer amount of seconds like `5` or a string of
+/// amounts with units like `"1m30s"`. Valid units are `ms`, `s`, `m`, and `h`.
+pub fn parse_duration(expr: &Expr) -> Result<u64> {
+    const EXPECTED: &str =
+        "expected a duration like `\"30s\"`, `\"1m30s\"`, or an amount of seconds";
+
+    let millis = match expr_lit(expr) {
+        Some(Lit::Int(lit)) => lit
+            .base10_parse::<u64>()?
+            .checked_mul(1000)
+            .ok_or(DurationError::Overflow),
+        Some(Lit::Str(lit)) => parse_duration_str(&lit.value()),
+        _ => Err(DurationError::Invalid),
+    };
+
+    match millis {
+        Ok(0) => Err(Error::new_spanned(expr, "duration must not be zero")),
+        Ok(millis) => Ok(millis),
+        Err(DurationError::Invalid) => Err(Error::new_spanned(expr, EXPECTED)),
+        Err(DurationError::Overflow) => Err(Error::new_spanned(expr, "duration is too long")),
+        Err(DurationError::Unit(unit)) => {
+            let content = match did_you_mean(&unit, UNITS) {
+                Some(suggestion) => format!("unknown unit `{unit}`, did you mean `{suggestion}`?"),
+                None => format!("unknown unit `{unit}`, expected `ms`, `s`, `m`, or `h`"),
+            };
+
+            Err(Error::new_spanned(expr, content))
+        }
+    }
+}
+
+const UNITS: &[&str] = &["h", "m", "s", "ms"];
+
+enum DurationError {
+    Invalid,
+    Overflow,
+    Unit(String),
+}
+
+fn parse_duration_str(s: &str) -> std::result::Result<u64, DurationError> {
+    let mut rest = s.trim();
+    let mut millis = 0_u64;
+
+    if rest.is_empty() {
+        return Err(DurationError::Invalid);
+    }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or(DurationError::Invalid)?;
+        let (amount, tail) = rest.split_at(digits);
+        let amount = amount.parse::<u64>().map_err(|_| DurationError::Invalid)?;
+
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+
+        let factor = match unit.trim() {
+            "ms" => 1,
+            "s" => 1000,
+            "m" => 60 * 1000,
+            "h" => 60 * 60 * 1000,
+            unit => return Err(DurationError::Unit(unit.to_owned())),
+        };
+
+        millis = amount
+            .checked_mul(factor)
+            .and_then(|amount| millis.checked_add(amount))
+            .ok_or(DurationError::Overflow)?;
+
+        rest = tail.trim_start();
+    }
+
+    Ok(millis)
+}
@@ -1,17 +1,47 @@
 mod command;
+mod duration;
+mod listener;
+mod suggest;
+mod task;
 
 use proc_macro::TokenStream;
 use syn::parse_macro_input;
 
-use crate::command::{attrs::CommandAttrs, model::command::CommandFn};
+use crate::{
+    command::{attrs::CommandAttrs, model::command::CommandFn},
+    listener::attrs::ListenerAttrs,
+    task::attrs::TaskAttrs,
+};
 
 #[proc_macro_attribute]
 pub fn command(attr: TokenStream, input: TokenStream) -> TokenStream {
     let cmd_attrs = parse_macro_input!(attr as CommandAttrs);
-    let cmd_fn = parse_macro_input!(input as CommandFn);
+    let cmd_fn = parse_macro_input!(input with CommandFn::parse_command);
 
     match command::impl_command(cmd_attrs, cmd_fn) {
         Ok(result) => result.into(),
         Err(err) => err.to_compile_error().into(),
     }
 }
+
+#[proc_macro_attribute]
+pub fn listener(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let listener_attrs = parse_macro_input!(attr as ListenerAttrs);
+    let listener_fn = parse_macro_input!(input with CommandFn::parse_listener);
+
+    match listener::impl_listener(listener_attrs, listener_fn) {
+        Ok(result) => result.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn task(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let task_attrs = parse_macro_input!(attr as TaskAttrs);
+    let task_fn = parse_macro_input!(input with CommandFn::parse_task);
+
+    match task::impl_task(task_attrs, task_fn) {
+        Ok(result) => result.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,155 @@
+use proc_macro2::{Ident, Span};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Error, Expr, ExprPath, Lit, LitStr, Meta, Path, Result, Token,
+};
+
+use crate::{command::attrs::expr_lit, suggest::did_you_mean};
+
+const KEYS: &[&str] = &["events", "platform", "regex"];
+const EVENTS: &[&str] = &["message", "action", "join", "reaction"];
+const PLATFORMS: &[&str] = &["irc", "matrix"];
+
+pub struct ListenerAttrs {
+    /// Variants of `EventKind`, all events if empty
+    pub events: Vec<Ident>,
+    /// Variant of `Platform`
+    pub platform: Option<Ident>,
+    pub regex: Option<ListenerRegex>,
+}
+
+pub enum ListenerRegex {
+    /// Pattern that's compiled into a static next to the listener
+    Pattern(LitStr),
+    /// Path of a `LazyLock<Regex>` static defined elsewhere, e.g. to share it
+    Static(Path),
+}
+
+impl Parse for ListenerAttrs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut events = Vec::new();
+        let mut platform = None;
+        let mut regex = None;
+
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        let mut seen = Vec::new();
+
+        for meta in metas {
+            let key = meta.path().get_ident().map(Ident::to_string);
+
+            if let Some(key) = key.as_ref().filter(|key| seen.contains(*key)) {
+                let content = format!("duplicate `{key}` attribute");
+
+                return Err(Error::new_spanned(meta.path(), content));
+            }
+
+            match (key.as_deref(), &meta) {
+                (Some("events"), Meta::List(list)) => {
+                    let lits = list.parse_args_with(
+                        Punctuated::<LitStr, Token![,]>::parse_separated_nonempty,
+                    )?;
+
+                    for lit in lits {
+                        let variant = variant(&lit, EVENTS, "event")?;
+
+                        if events.contains(&variant) {
+                            return Err(Error::new_spanned(lit, "duplicate event"));
+                        }
+
+                        events.push(variant);
+                    }
+                }
+                (Some("platform"), Meta::NameValue(name_value)) => {
+                    let lit = string_lit(&name_value.value)?;
+                    platform = Some(variant(lit, PLATFORMS, "platform")?);
+                }
+                (Some("regex"), Meta::NameValue(name_value)) => {
+                    let value = match &name_value.value {
+                        Expr::Path(ExprPath { path, .. }) => ListenerRegex::Static(path.to_owned()),
+                        value => {
+                            let lit = string_lit(value)?;
+
+                            // fail at compile time rather than on the first message
+                            if let Err(err) = regex::Regex::new(&lit.value()) {
+                                let content = format!("invalid regex: {err}");
+
+                                return Err(Error::new_spanned(lit, content));
+                            }
+
+                            ListenerRegex::Pattern(lit.to_owned())
+                        }
+                    };
+
+                    regex = Some(value);
+                }
+                (Some("events"), _) => {
+                    return Err(Error::new_spanned(meta, "expected `events(\"...\", ...)`"));
+                }
+                (Some(key @ ("platform" | "regex")), _) => {
+                    let content = format!("expected `{key} = \"...\"`");
+
+                    return Err(Error::new_spanned(meta, content));
+                }
+                _ => {
+                    let suggestion = key.as_deref().and_then(|key| did_you_mean(key, KEYS));
+
+                    let content = match suggestion {
+                        Some(key) => format!("unknown attribute, did you mean `{key}`?"),
+                        None => "expected `events(...)`, `platform = \"...\"`, \
+                            or `regex = \"...\"`"
+                            .to_owned(),
+                    };
+
+                    return Err(Error::new_spanned(meta.path(), content));
+                }
+            }
+
+            seen.extend(key);
+        }
+
+        Ok(Self {
+            events,
+            platform,
+            regex,
+        })
+    }
+}
+
+fn string_lit(expr: &Expr) -> Result<&LitStr> {
+    match expr_lit(expr) {
+        Some(Lit::Str(lit)) => Ok(lit),
+        _ => Err(Error::new_spanned(expr, "expected a string literal")),
+    }
+}
+
+/// Turn a value like `"message"` into the enum variant `Message`.
+fn variant(lit: &LitStr, values: &[&str], what: &str) -> Result<Ident> {
+    let value = lit.value();
+
+    if !values.contains(&value.as_str()) {
+        let content = match did_you_mean(&value, values) {
+            Some(suggestion) => format!("unknown {what}, did you mean `\"{suggestion}\"`?"),
+            None => {
+                let expected: Vec<_> = values
+                    .iter()
+                    .map(|value| format!("`\"{value}\"`"))
+                    .collect();
+
+                format!("unknown {what}, expected one of {}", expected.join(", "))
+            }
+        };
+
+        return Err(Error::new_spanned(lit, content));
+    }
+
+    let mut chars = value.chars();
+    let variant: String = chars
+        .next()
+        .map(|first| first.to_ascii_uppercase())
+        .into_iter()
+        .chain(chars)
+        .collect();
+
+    Ok(Ident::new(&variant, Span::call_site()))
+}
@@ -0,0 +1,98 @@
+pub mod attrs;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::Result;
+
+use crate::command::model::command::CommandFn;
+
+use self::attrs::{ListenerAttrs, ListenerRegex};
+
+pub fn impl_listener(listener_attrs: ListenerAttrs, listener_fn: CommandFn) -> Result<TokenStream> {
+    let ListenerAttrs {
+        events,
+        platform,
+        regex,
+    } = listener_attrs;
+
+    let CommandFn {
+        attrs,
+        vis,
+        async_token,
+        fn_token,
+        name: listener_ident,
+        args: listener_args,
+        ret,
+        body,
+    } = listener_fn;
+
+    let mut run_args = listener_args.clone();
+    run_args.ensure_names();
+    let ctx_name = &run_args.ctx.name;
+    let orig_name = &run_args.orig.name;
+    let event_name = &run_args.args.name;
+
+    let ret_ty = &ret.ty;
+
+    let listener_name = listener_ident.to_string();
+
+    let static_name = format_ident!(
+        "{}",
+        listener_name.to_uppercase(),
+        span = listener_ident.span()
+    );
+
+    let run_fn_name = format_ident!("run_{listener_name}");
+
+    let location = quote_spanned!(listener_ident.span()=> concat!(file!(), ":", line!()));
+
+    let listener_slice_path = quote!(crate::LISTENERS_SLICE);
+    let listener_path = quote!(crate::Listener);
+    let box_fut_path = quote!(::futures::future::BoxFuture);
+
+    let event_kind_path = quote!(crate::EventKind);
+    let events = events.iter().map(|event| quote!(#event_kind_path::#event));
+
+    let platform = match platform {
+        Some(platform) => quote!(Some(crate::Platform::#platform)),
+        None => quote!(None),
+    };
+
+    let (regex, regex_static) = match regex {
+        Some(ListenerRegex::Static(path)) => (quote!(Some(&#path)), TokenStream::new()),
+        Some(ListenerRegex::Pattern(pattern)) => {
+            let regex_name = format_ident!("{static_name}_REGEX");
+
+            let regex_static = quote! {
+                static #regex_name: ::std::sync::LazyLock<::regex::Regex> =
+                    ::std::sync::LazyLock::new(|| ::regex::Regex::new(#pattern).unwrap());
+            };
+
+            (quote!(Some(&#regex_name)), regex_static)
+        }
+        None => (quote!(None), TokenStream::new()),
+    };
+
+    let tokens = quote! {
+        #[linkme::distributed_slice( #listener_slice_path )]
+        pub static #static_name: #listener_path = #listener_path {
+            name: #listener_name,
+            events: &[ #( #events ),* ],
+            platform: #platform,
+            regex: #regex,
+            location: #location,
+            run: #run_fn_name,
+        };
+
+        #regex_static
+
+        fn #run_fn_name<'fut>( #run_args ) -> #box_fut_path<'fut, #ret_ty> {
+            Box::pin( #listener_ident( #ctx_name, #orig_name, #event_name ) )
+        }
+
+        #(#attrs)*
+        #vis #async_token #fn_token #listener_ident <'fut> ( #listener_args ) #ret #body
+    };
+
+    Ok(tokens)
+}
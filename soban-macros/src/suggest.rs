@@ -0,0 +1,13 @@
+use soban_suggest::levenshtein;
+
+/// Find the candidate closest to `input`, if any is close enough to likely be a typo.
+pub fn did_you_mean<'c>(input: &str, candidates: &[&'c str]) -> Option<&'c str> {
+    let max_distance = input.chars().count().div_ceil(3);
+
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, levenshtein(input, candidate)))
+        .filter(|(_, distance)| *distance <= max_distance)
+        .min_by_key(|(_, distance)| *distance)
+        .map(|(candidate, _)| candidate)
+}
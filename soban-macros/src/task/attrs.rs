@@ -0,0 +1,82 @@
+use proc_macro2::{Ident, Span};
+use syn::{
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    Error, Meta, Result, Token,
+};
+
+use crate::{duration::parse_duration, suggest::did_you_mean};
+
+const KEYS: &[&str] = &["every", "jitter"];
+
+pub struct TaskAttrs {
+    /// Interval between runs in milliseconds
+    pub every: u64,
+    /// Maximum random delay added to each interval in milliseconds
+    pub jitter: u64,
+}
+
+impl Parse for TaskAttrs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut every = None;
+        let mut jitter = None;
+
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        let mut seen = Vec::new();
+
+        for meta in metas {
+            let key = meta.path().get_ident().map(Ident::to_string);
+
+            if let Some(key) = key.as_ref().filter(|key| seen.contains(*key)) {
+                let content = format!("duplicate `{key}` attribute");
+
+                return Err(Error::new_spanned(meta.path(), content));
+            }
+
+            match (key.as_deref(), &meta) {
+                (Some("every"), Meta::NameValue(name_value)) => {
+                    every = Some(parse_duration(&name_value.value)?);
+                }
+                (Some("jitter"), Meta::NameValue(name_value)) => {
+                    jitter = Some((parse_duration(&name_value.value)?, name_value.value.clone()));
+                }
+                (Some(key @ ("every" | "jitter")), _) => {
+                    let content = format!("expected `{key} = \"...\"`");
+
+                    return Err(Error::new_spanned(meta, content));
+                }
+                _ => {
+                    let suggestion = key.as_deref().and_then(|key| did_you_mean(key, KEYS));
+
+                    let content = match suggestion {
+                        Some(key) => format!("unknown attribute, did you mean `{key}`?"),
+                        None => "expected `every = \"...\"` or `jitter = \"...\"`".to_owned(),
+                    };
+
+                    return Err(Error::new_spanned(meta.path(), content));
+                }
+            }
+
+            seen.extend(key);
+        }
+
+        let Some(every) = every else {
+            let content = "missing interval, add e.g. `every = \"5m\"`";
+
+            return Err(Error::new(Span::call_site(), content));
+        };
+
+        let jitter = match jitter {
+            Some((jitter, expr)) if jitter >= every => {
+                let content = "jitter must be shorter than the interval";
+
+                return Err(Error::new_spanned(expr, content));
+            }
+            Some((jitter, _)) => jitter,
+            // spreads out tasks with the same interval by default
+            None => every / 10,
+        };
+
+        Ok(Self { every, jitter })
+    }
+}
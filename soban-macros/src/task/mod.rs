@@ -0,0 +1,64 @@
+pub mod attrs;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::Result;
+
+use crate::command::model::{args::TaskArgs, command::CommandFn};
+
+use self::attrs::TaskAttrs;
+
+pub fn impl_task(task_attrs: TaskAttrs, task_fn: CommandFn<TaskArgs>) -> Result<TokenStream> {
+    let TaskAttrs { every, jitter } = task_attrs;
+
+    let CommandFn {
+        attrs,
+        vis,
+        async_token,
+        fn_token,
+        name: task_ident,
+        args: task_args,
+        ret,
+        body,
+    } = task_fn;
+
+    let mut run_args = task_args.clone();
+    run_args.ensure_names();
+    let ctx_name = &run_args.ctx.name;
+    let channels_name = &run_args.channels.name;
+
+    let ret_ty = &ret.ty;
+
+    let task_name = task_ident.to_string();
+
+    let static_name = format_ident!("{}", task_name.to_uppercase(), span = task_ident.span());
+
+    let run_fn_name = format_ident!("run_{task_name}");
+
+    let location = quote_spanned!(task_ident.span()=> concat!(file!(), ":", line!()));
+
+    let task_slice_path = quote!(crate::TASKS_SLICE);
+    let task_path = quote!(crate::Task);
+    let box_fut_path = quote!(::futures::future::BoxFuture);
+    let duration_path = quote!(::std::time::Duration);
+
+    let tokens = quote! {
+        #[linkme::distributed_slice( #task_slice_path )]
+        pub static #static_name: #task_path = #task_path {
+            name: #task_name,
+            every: #duration_path::from_millis(#every),
+            jitter: #duration_path::from_millis(#jitter),
+            location: #location,
+            run: #run_fn_name,
+        };
+
+        fn #run_fn_name( #run_args ) -> #box_fut_path<'static, #ret_ty> {
+            Box::pin( #task_ident( #ctx_name, #channels_name ) )
+        }
+
+        #(#attrs)*
+        #vis #async_token #fn_token #task_ident ( #task_args ) #ret #body
+    };
+
+    Ok(tokens)
+}
@@ -0,0 +1,38 @@
+use soban_macros::command;
+
+#[command]
+async fn missing(ctx: Arc<Context>, origin: CommandOrigin<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn too_many(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>, extra: u32) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn ref_context(ctx: &Context, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn wrong_context(ctx: Arc<Client>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn named_lifetime(ctx: Arc<Context>, origin: CommandOrigin<'a>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn wrong_origin(ctx: Arc<Context>, origin: Origin, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn str_args(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: &str) -> Result<()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,58 @@
+use soban_macros::command;
+
+#[command(alias("p"))]
+async fn typo(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(description = "pong")]
+async fn unknown(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown("3s"))]
+async fn wrong_shape(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(aliases = "p")]
+async fn aliases_value(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown = "3s", cooldown = "5s")]
+async fn duplicate(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(aliases("p", "p"))]
+async fn duplicate_alias(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(aliases("p", "P"))]
+async fn duplicate_alias_case(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(aliases("Ping"))]
+async fn ping(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(aliases("two words"))]
+async fn spaced_alias(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(group = "two words")]
+async fn spaced_group(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(group = "track", name = "add", aliases("add"))]
+async fn track_add(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use soban_macros::command;
+
+#[command(cooldown = "3mn")]
+async fn unit_typo(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown = "3 days")]
+async fn unknown_unit(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown = 0)]
+async fn zero(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown = "s")]
+async fn missing_amount(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(cooldown = true)]
+async fn not_duration(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+fn main() {}
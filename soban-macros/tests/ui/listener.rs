@@ -0,0 +1,38 @@
+use soban_macros::listener;
+
+#[listener(regex = "osu\\.ppy\\.sh/b/(\\d+")]
+async fn bad_regex(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener(events("mesage"))]
+async fn event_typo(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener(events("join", "join"))]
+async fn duplicate_event(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener(platform = "discord")]
+async fn unknown_platform(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener(regx = ".*")]
+async fn key_typo(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener]
+async fn args_instead_of_event(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[listener]
+async fn replying(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<Reply> {
+    Ok(Reply::None)
+}
+
+fn main() {}
@@ -0,0 +1,86 @@
+//! Listeners are registered in their own slice with their filters.
+
+use soban_macros::listener;
+
+pub struct Context;
+
+pub struct CommandOrigin<'a>(&'a str);
+
+pub struct Event<'a> {
+    pub text: &'a str,
+    pub captures: Option<regex::Captures<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EventKind {
+    Message,
+    Action,
+    Join,
+    Reaction,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Platform {
+    Irc,
+    Matrix,
+}
+
+type ListenerFn = for<'a> fn(
+    std::sync::Arc<Context>,
+    CommandOrigin<'a>,
+    Event<'a>,
+) -> futures::future::BoxFuture<'a, eyre::Result<()>>;
+
+pub struct Listener {
+    pub name: &'static str,
+    pub events: &'static [EventKind],
+    pub platform: Option<Platform>,
+    pub regex: Option<&'static std::sync::LazyLock<regex::Regex>>,
+    pub location: &'static str,
+    pub run: ListenerFn,
+}
+
+#[linkme::distributed_slice]
+pub static LISTENERS_SLICE: [Listener] = [..];
+
+#[listener(events("message", "action"), platform = "irc", regex = r"b/(\d+)")]
+async fn filtered(
+    _ctx: std::sync::Arc<Context>,
+    origin: CommandOrigin<'_>,
+    event: Event<'_>,
+) -> eyre::Result<()> {
+    let _ = (origin.0, event.text, event.captures);
+
+    Ok(())
+}
+
+static LINK: std::sync::LazyLock<regex::Regex> =
+    std::sync::LazyLock::new(|| regex::Regex::new(r"s/(\d+)").unwrap());
+
+#[listener(regex = LINK)]
+async fn shared(_: std::sync::Arc<Context>, _: CommandOrigin<'_>, _: Event<'_>) -> eyre::Result<()> {
+    Ok(())
+}
+
+#[listener]
+async fn everything(_: std::sync::Arc<Context>, _: CommandOrigin<'_>, _: Event<'_>) -> eyre::Result<()> {
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(LISTENERS_SLICE.len(), 3);
+
+    assert_eq!(FILTERED.name, "filtered");
+    assert_eq!(FILTERED.events, [EventKind::Message, EventKind::Action]);
+    assert_eq!(FILTERED.platform, Some(Platform::Irc));
+    let captures = FILTERED.regex.unwrap().captures("osu.ppy.sh/b/42").unwrap();
+    assert_eq!(&captures[1], "42");
+
+    assert!(std::ptr::eq(SHARED.regex.unwrap(), &LINK));
+
+    assert!(EVERYTHING.events.is_empty());
+    assert_eq!(EVERYTHING.platform, None);
+    assert!(EVERYTHING.regex.is_none());
+    assert!(EVERYTHING.location.contains("listener.rs:"), "{}", EVERYTHING.location);
+    let _ = (EventKind::Join, EventKind::Reaction, Platform::Matrix, EVERYTHING.run);
+}
@@ -0,0 +1,114 @@
+//! Types are matched by their last path segment so they don't have to be imported.
+
+use soban_macros::command;
+
+pub struct Context;
+
+pub struct CommandOrigin<'a>(&'a str);
+
+pub struct Args<'a> {
+    pub msg: &'a str,
+}
+
+pub enum Permission {
+    Everyone,
+    Moderator,
+    Admin,
+    Owner,
+    Role(&'static str),
+}
+
+pub enum Reply {
+    None,
+    Text(String),
+}
+
+impl From<()> for Reply {
+    fn from(_: ()) -> Self {
+        Self::None
+    }
+}
+
+type CommandFn = for<'a> fn(
+    std::sync::Arc<Context>,
+    CommandOrigin<'a>,
+    Args<'a>,
+) -> futures::future::BoxFuture<'a, eyre::Result<Reply>>;
+
+pub struct Command {
+    pub name: &'static str,
+    pub group: Option<&'static str>,
+    pub qualified_name: &'static str,
+    pub description: &'static str,
+    pub aliases: &'static [&'static str],
+    pub cooldown: Option<std::time::Duration>,
+    pub permission: Permission,
+    pub location: &'static str,
+    pub run: CommandFn,
+}
+
+#[linkme::distributed_slice]
+pub static COMMANDS_SLICE: [Command] = [..];
+
+/// Doc comments are kept.
+#[command(aliases("p"), cooldown = "1m 30s", permission = "role:helper")]
+pub async fn plain(
+    _ctx: std::sync::Arc<Context>,
+    origin: crate::CommandOrigin<'_>,
+    args: self::Args,
+) -> eyre::Result<()> {
+    let _ = (origin.0, args.msg);
+
+    Ok(())
+}
+
+#[command(cooldown = 5, permission = "mod")]
+async fn full_error(
+    _: std::sync::Arc<crate::Context>,
+    _: CommandOrigin<'_>,
+    _: Args<'_>,
+) -> std::result::Result<(), eyre::Report> {
+    Ok(())
+}
+
+/// Replies are sent by the dispatcher.
+#[command]
+async fn replying(_: std::sync::Arc<Context>, _: CommandOrigin<'_>, _: Args<'_>) -> eyre::Result<crate::Reply> {
+    Ok(Reply::Text("hi".to_owned()))
+}
+
+/// Subcommands are
+/// named by their attribute.
+///
+/// Only the first paragraph is kept.
+#[command(group = "plain", name = "sub")]
+async fn plain_sub(
+    _: std::sync::Arc<Context>,
+    _: CommandOrigin<'_>,
+    _: Args<'_>,
+) -> eyre::Result<()> {
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(COMMANDS_SLICE.len(), 4);
+
+    assert_eq!(PLAIN_SUB.name, "sub");
+    assert_eq!(PLAIN_SUB.group, Some("plain"));
+    assert_eq!(PLAIN_SUB.qualified_name, "plain sub");
+    assert_eq!(PLAIN_SUB.description, "Subcommands are named by their attribute.");
+    assert_eq!(PLAIN.description, "Doc comments are kept.");
+    assert_eq!(PLAIN.qualified_name, "plain");
+
+    let names: Vec<_> = COMMANDS_SLICE.iter().map(|cmd| cmd.name).collect();
+    assert!(names.contains(&"plain"));
+    assert!(names.contains(&"full_error"));
+
+    assert_eq!(PLAIN.aliases, ["p"]);
+    assert_eq!(PLAIN.cooldown, Some(std::time::Duration::from_secs(90)));
+    assert!(matches!(PLAIN.permission, Permission::Role("helper")));
+    assert!(matches!(FULL_ERROR.permission, Permission::Moderator));
+    let _ = (Permission::Everyone, Permission::Admin, Permission::Owner);
+    assert!(PLAIN.location.contains("paths.rs:"), "{}", PLAIN.location);
+    let _ = (PLAIN.run, REPLYING.run);
+}
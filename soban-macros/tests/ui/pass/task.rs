@@ -0,0 +1,48 @@
+//! Tasks are registered in their own slice with their interval.
+
+use std::time::Duration;
+
+use soban_macros::task;
+
+pub struct Context;
+
+pub struct Channels;
+
+type TaskFn =
+    fn(std::sync::Arc<Context>, Channels) -> futures::future::BoxFuture<'static, eyre::Result<()>>;
+
+pub struct Task {
+    pub name: &'static str,
+    pub every: Duration,
+    pub jitter: Duration,
+    pub location: &'static str,
+    pub run: TaskFn,
+}
+
+#[linkme::distributed_slice]
+pub static TASKS_SLICE: [Task] = [..];
+
+#[task(every = "5m")]
+async fn default_jitter(_ctx: std::sync::Arc<Context>, _: Channels) -> eyre::Result<()> {
+    Ok(())
+}
+
+#[task(every = 60, jitter = "500ms")]
+async fn custom_jitter(_: std::sync::Arc<Context>, channels: self::Channels) -> eyre::Result<()> {
+    let _ = channels;
+
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(TASKS_SLICE.len(), 2);
+
+    assert_eq!(DEFAULT_JITTER.name, "default_jitter");
+    assert_eq!(DEFAULT_JITTER.every, Duration::from_secs(5 * 60));
+    assert_eq!(DEFAULT_JITTER.jitter, Duration::from_secs(30));
+    assert!(DEFAULT_JITTER.location.contains("task.rs:"), "{}", DEFAULT_JITTER.location);
+
+    assert_eq!(CUSTOM_JITTER.every, Duration::from_secs(60));
+    assert_eq!(CUSTOM_JITTER.jitter, Duration::from_millis(500));
+    let _ = CUSTOM_JITTER.run;
+}
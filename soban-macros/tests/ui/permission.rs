@@ -0,0 +1,23 @@
+use soban_macros::command;
+
+#[command(permission = "admn")]
+async fn typo(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(permission = "nobody")]
+async fn unknown(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(permission = "role:")]
+async fn empty_role(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command(permission = owner)]
+async fn not_string(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+use soban_macros::command;
+
+#[command]
+fn not_async(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn generic<T>(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[command]
+async fn missing_return(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) {}
+
+#[command]
+async fn wrong_return(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<String> {
+    Ok(String::new())
+}
+
+#[command]
+async fn not_result(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Option<()> {
+    None
+}
+
+fn main() {}
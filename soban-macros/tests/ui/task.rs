@@ -0,0 +1,33 @@
+use soban_macros::task;
+
+#[task]
+async fn missing_every(ctx: Arc<Context>, channels: Channels) -> Result<()> {
+    Ok(())
+}
+
+#[task(every = "5m", jitter = "5m")]
+async fn long_jitter(ctx: Arc<Context>, channels: Channels) -> Result<()> {
+    Ok(())
+}
+
+#[task(evry = "5m")]
+async fn key_typo(ctx: Arc<Context>, channels: Channels) -> Result<()> {
+    Ok(())
+}
+
+#[task(every = "5m")]
+async fn with_origin(ctx: Arc<Context>, origin: CommandOrigin<'_>) -> Result<()> {
+    Ok(())
+}
+
+#[task(every = "5m")]
+async fn only_ctx(ctx: Arc<Context>) -> Result<()> {
+    Ok(())
+}
+
+#[task(every = "5m")]
+async fn returns_string(ctx: Arc<Context>, channels: Channels) -> Result<String> {
+    Ok(String::new())
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+//! Edit distance for suggesting names, shared by the bot and its macros.
+
+/// Number of single-character insertions, deletions and substitutions to
+/// turn `a` into `b`.
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut prev_diag = row[0];
+        row[0] = i + 1;
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = prev_diag + usize::from(a != *b);
+            prev_diag = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(prev_diag + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_edits() {
+        assert_eq!(levenshtein("recent", "recent"), 0);
+        assert_eq!(levenshtein("recnet", "recent"), 2);
+        assert_eq!(levenshtein("", "osu"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("ösu", "osu"), 1);
+    }
+}
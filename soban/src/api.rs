@@ -0,0 +1,85 @@
+use futures::{future::BoxFuture, FutureExt};
+use rosu_v2::{
+    prelude::{Beatmap, GameMode, OsuResult, Rankings, Score, User},
+    request::UserId,
+    Osu,
+};
+
+/// All osu! requests of the bot go through here so that tests can replace
+/// the osu!api with a local stand-in.
+///
+/// [`Osu`] talks to the live osu!api. Its base url can't be configured, so
+/// stand-ins implement this trait themselves.
+pub trait OsuApi: Send + Sync {
+    fn user(&self, user_id: UserId) -> BoxFuture<'_, OsuResult<User>>;
+
+    fn recent_scores(
+        &self,
+        user_id: u32,
+        include_fails: bool,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<'_, OsuResult<Vec<Score>>>;
+
+    /// The user's top plays, sorted by pp.
+    fn best_scores(&self, user_id: u32, limit: usize) -> BoxFuture<'_, OsuResult<Vec<Score>>>;
+
+    /// A page of 50 users of the osu!standard pp leaderboard, globally or for
+    /// the country.
+    fn performance_rankings<'a>(
+        &'a self,
+        country: Option<&'a str>,
+        page: u32,
+    ) -> BoxFuture<'a, OsuResult<Rankings>>;
+
+    fn beatmap(&self, map_id: u32) -> BoxFuture<'_, OsuResult<Beatmap>>;
+
+    /// Url to download the `.osu` file of a map from.
+    fn osu_file_url(&self, map_id: u32) -> String;
+}
+
+impl OsuApi for Osu {
+    fn user(&self, user_id: UserId) -> BoxFuture<'_, OsuResult<User>> {
+        Osu::user(self, user_id).boxed()
+    }
+
+    fn recent_scores(
+        &self,
+        user_id: u32,
+        include_fails: bool,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<'_, OsuResult<Vec<Score>>> {
+        self.user_scores(user_id)
+            .recent()
+            .include_fails(include_fails)
+            .offset(offset)
+            .limit(limit)
+            .boxed()
+    }
+
+    fn best_scores(&self, user_id: u32, limit: usize) -> BoxFuture<'_, OsuResult<Vec<Score>>> {
+        self.user_scores(user_id).best().limit(limit).boxed()
+    }
+
+    fn performance_rankings<'a>(
+        &'a self,
+        country: Option<&'a str>,
+        page: u32,
+    ) -> BoxFuture<'a, OsuResult<Rankings>> {
+        let request = Osu::performance_rankings(self, GameMode::Osu).page(page);
+
+        match country {
+            Some(country) => request.country(country).boxed(),
+            None => request.boxed(),
+        }
+    }
+
+    fn beatmap(&self, map_id: u32) -> BoxFuture<'_, OsuResult<Beatmap>> {
+        Osu::beatmap(self).map_id(map_id).boxed()
+    }
+
+    fn osu_file_url(&self, map_id: u32) -> String {
+        format!("https://osu.ppy.sh/osu/{map_id}")
+    }
+}
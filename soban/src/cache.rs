@@ -0,0 +1,258 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use rosu_v2::{
+    prelude::{Beatmap, OsuError, RankStatus, Rankings, User},
+    request::UserId,
+};
+use tokio::sync::OnceCell;
+
+use crate::OsuApi;
+
+const USER_TTL: Duration = Duration::from_secs(5 * 60);
+const RANKED_MAP_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+const UNRANKED_MAP_TTL: Duration = Duration::from_secs(10 * 60);
+const RANKINGS_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Amount of entries after which expired ones are purged on the next insert
+const PURGE_THRESHOLD: usize = 256;
+
+/// Caches osu!api responses in front of [`OsuApi`].
+///
+/// Identical requests that arrive while one is already in flight wait for
+/// that request instead of hitting the api again.
+#[derive(Default)]
+pub struct OsuCache {
+    users: TtlCache<UserKey, User>,
+    maps: TtlCache<u32, Beatmap>,
+    /// Leaderboard pages by country and page number
+    rankings: TtlCache<(Option<String>, u32), Rankings>,
+}
+
+impl OsuCache {
+    /// Get a user, fetching it if it's not cached, expired, or `fresh` is set.
+    pub async fn user(
+        &self,
+        osu: &dyn OsuApi,
+        user_id: UserId,
+        fresh: bool,
+    ) -> Result<Arc<User>, OsuError> {
+        let key = UserKey::from(&user_id);
+
+        self.users
+            .get_or_fetch(key, fresh, |_| USER_TTL, osu.user(user_id))
+            .await
+    }
+
+    /// Get a beatmap, fetching it if it's not cached, expired, or `fresh` is set.
+    pub async fn beatmap(
+        &self,
+        osu: &dyn OsuApi,
+        map_id: u32,
+        fresh: bool,
+    ) -> Result<Arc<Beatmap>, OsuError> {
+        self.maps
+            .get_or_fetch(
+                map_id,
+                fresh,
+                |map| status_ttl(map.status),
+                osu.beatmap(map_id),
+            )
+            .await
+    }
+
+    /// Get a page of the pp leaderboard, fetching it if it's not cached or expired.
+    pub async fn rankings(
+        &self,
+        osu: &dyn OsuApi,
+        country: Option<&str>,
+        page: u32,
+    ) -> Result<Arc<Rankings>, OsuError> {
+        let key = (country.map(str::to_uppercase), page);
+
+        self.rankings
+            .get_or_fetch(
+                key,
+                false,
+                |_| RANKINGS_TTL,
+                osu.performance_rankings(country, page),
+            )
+            .await
+    }
+
+    /// Remove all expired entries.
+    pub fn purge(&self) {
+        self.users.purge();
+        self.maps.purge();
+        self.rankings.purge();
+    }
+
+    /// Amount of cached users and maps, including expired ones.
+    pub fn sizes(&self) -> CacheSizes {
+        CacheSizes {
+            users: self.users.len(),
+            maps: self.maps.len(),
+        }
+    }
+}
+
+pub struct CacheSizes {
+    pub users: usize,
+    pub maps: usize,
+}
+
+fn status_ttl(status: RankStatus) -> Duration {
+    match status {
+        RankStatus::Ranked | RankStatus::Approved | RankStatus::Loved => RANKED_MAP_TTL,
+        RankStatus::Graveyard | RankStatus::WIP | RankStatus::Pending | RankStatus::Qualified => {
+            UNRANKED_MAP_TTL
+        }
+    }
+}
+
+/// Usernames are case-insensitive so they're normalized before being used as key.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum UserKey {
+    Id(u32),
+    Name(String),
+}
+
+impl From<&UserId> for UserKey {
+    fn from(user_id: &UserId) -> Self {
+        match user_id {
+            UserId::Id(id) => Self::Id(*id),
+            UserId::Name(name) => Self::Name(name.to_lowercase()),
+        }
+    }
+}
+
+struct Entry<V> {
+    value: Arc<V>,
+    expires_at: Instant,
+}
+
+type Slot<V> = Arc<OnceCell<Entry<V>>>;
+
+struct TtlCache<K, V> {
+    entries: Mutex<HashMap<K, Slot<V>>>,
+}
+
+impl<K, V> Default for TtlCache<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> TtlCache<K, V> {
+    async fn get_or_fetch<F>(
+        &self,
+        key: K,
+        fresh: bool,
+        ttl: impl FnOnce(&V) -> Duration,
+        fetch: F,
+    ) -> Result<Arc<V>, OsuError>
+    where
+        F: Future<Output = Result<V, OsuError>>,
+    {
+        let slot = self.slot(key, fresh);
+
+        let entry = slot
+            .get_or_try_init(|| async {
+                let value = fetch.await?;
+                let expires_at = Instant::now() + ttl(&value);
+
+                Ok::<_, OsuError>(Entry {
+                    value: Arc::new(value),
+                    expires_at,
+                })
+            })
+            .await?;
+
+        Ok(Arc::clone(&entry.value))
+    }
+
+    /// Get the slot for the key, replacing it if its value is stale.
+    ///
+    /// A slot that's still being initialized is shared so that concurrent
+    /// requests are coalesced, even if `fresh` is set.
+    fn slot(&self, key: K, fresh: bool) -> Slot<V> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+
+        if entries.len() >= PURGE_THRESHOLD {
+            retain_fresh(&mut entries, now);
+        }
+
+        let slot = entries.entry(key).or_default();
+
+        let stale = slot
+            .get()
+            .is_some_and(|entry| fresh || entry.expires_at <= now);
+
+        if stale {
+            *slot = Slot::default();
+        }
+
+        Arc::clone(slot)
+    }
+
+    fn purge(&self) {
+        retain_fresh(&mut self.entries.lock().unwrap(), Instant::now());
+    }
+
+    fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+}
+
+/// Remove expired entries and abandoned slots whose fetch failed.
+fn retain_fresh<K, V>(entries: &mut HashMap<K, Slot<V>>, now: Instant) {
+    entries.retain(|_, slot| match slot.get() {
+        Some(entry) => entry.expires_at > now,
+        None => Arc::strong_count(slot) > 1,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{TestContext, USERNAME, USER_ID};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_requests_are_coalesced() {
+        let test = TestContext::new().await;
+        let ctx = &test.ctx;
+
+        let (a, b) = tokio::join!(
+            ctx.cache.user(&*ctx.osu, UserId::Id(USER_ID), false),
+            ctx.cache.user(&*ctx.osu, UserId::Id(USER_ID), false),
+        );
+
+        assert!(Arc::ptr_eq(&a.unwrap(), &b.unwrap()));
+        assert_eq!(test.stand_in.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn usernames_are_case_insensitive() {
+        let test = TestContext::new().await;
+        let ctx = &test.ctx;
+
+        for name in [USERNAME, &USERNAME.to_uppercase()] {
+            ctx.cache
+                .user(&*ctx.osu, UserId::Name(name.into()), false)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(test.stand_in.requests().len(), 1);
+        assert_eq!(ctx.cache.sizes().users, 1);
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use matrix_sdk::ruma::{OwnedRoomId, RoomAliasId, RoomId, RoomOrAliasId};
+use soban_macros::command;
+
+use crate::{
+    normalize_user_key, owners_from_env, utils::datetime::format_duration, Args, CommandOrigin,
+    Context, Permission, Platform, Reply,
+};
+
+const ROLE_USAGE: &str = "role <add|remove|list> <user> [role]";
+
+/// `!role add <user> <role>`, `!role remove <user> <role>`, or `!role list <user>`
+///
+/// Users are given as `platform:account`, e.g. `irc:account` or `matrix:@user:example.com`.
+/// If the platform is omitted, the platform of the command is used.
+#[command(permission = "admin")]
+async fn role(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let mut words = args.msg.split_whitespace();
+
+    let (Some(action), Some(user)) = (words.next(), words.next()) else {
+        return Ok(Reply::text(ctx.usage(&origin, ROLE_USAGE).await));
+    };
+
+    let user = if user.starts_with("irc:") || user.starts_with("matrix:") {
+        normalize_user_key(user)
+    } else {
+        normalize_user_key(&format!("{}:{user}", origin.platform()))
+    };
+
+    if action == "list" {
+        let roles = ctx.db.roles(&user).await;
+
+        let response = if roles.is_empty() {
+            format!("{user} has no roles")
+        } else {
+            let roles = roles.into_iter().collect::<Vec<_>>().join(", ");
+
+            format!("{user} has the roles {roles}")
+        };
+
+        return Ok(Reply::text(response));
+    }
+
+    let Some(role) = words.next() else {
+        return Ok(Reply::text("missing role"));
+    };
+
+    // only owners may hand out or take away admin
+    if role == "admin" && !Permission::Owner.is_granted(&ctx, &origin).await? {
+        return Ok(Reply::text("only owners can manage the admin role"));
+    }
+
+    let response = match action {
+        "add" if ctx.db.add_role(&user, role).await? => format!("gave {user} the role {role}"),
+        "add" => format!("{user} already has the role {role}"),
+        "remove" if ctx.db.remove_role(&user, role).await? => {
+            format!("removed the role {role} from {user}")
+        }
+        "remove" => format!("{user} doesn't have the role {role}"),
+        _ => ctx.usage(&origin, ROLE_USAGE).await,
+    };
+
+    Ok(Reply::text(response))
+}
+
+/// `!join [irc|matrix] <channel>`
+///
+/// Joined irc channels are stored in the database and rejoined on startup.
+/// Matrix rooms are remembered by the homeserver.
+#[command(permission = "owner")]
+async fn join(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let (platform, channel) = split_platform(&origin, args.msg);
+
+    if channel.is_empty() {
+        let usage = ctx.usage(&origin, "join [irc|matrix] <channel>").await;
+
+        return Ok(Reply::text(usage));
+    }
+
+    match platform {
+        Platform::Irc => {
+            let Some(sender) = ctx.platforms.irc.get() else {
+                return Ok(Reply::text("irc is not connected"));
+            };
+
+            if !channel.starts_with(['#', '&']) {
+                return Ok(Reply::text("irc channels must start with `#` or `&`"));
+            }
+
+            sender.send_join(channel)?;
+            ctx.db.add_irc_channel(channel).await?;
+        }
+        Platform::Matrix => {
+            let Some(client) = ctx.platforms.matrix.get() else {
+                return Ok(Reply::text("matrix is not connected"));
+            };
+
+            let Ok(room) = <&RoomOrAliasId>::try_from(channel) else {
+                return Ok(Reply::text("invalid room id or alias"));
+            };
+
+            client.join_room_by_id_or_alias(room, &[]).await?;
+        }
+    }
+
+    info!(%platform, channel, "Joined channel");
+
+    Ok(Reply::text(format!("joined {channel} on {platform}")))
+}
+
+const PART_USAGE: &str = "part [irc|matrix] [channel]";
+
+/// `!part [irc|matrix] [channel]`
+///
+/// Leaves the current channel if none is specified.
+#[command(permission = "owner")]
+async fn part(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let (platform, channel) = split_platform(&origin, args.msg);
+
+    match platform {
+        Platform::Irc => {
+            let Some(sender) = ctx.platforms.irc.get() else {
+                return Ok(Reply::text("irc is not connected"));
+            };
+
+            let channel = match (origin.platform(), channel) {
+                (Platform::Irc, "") => origin.channel(),
+                (_, "") => return Ok(Reply::text(ctx.usage(&origin, PART_USAGE).await)),
+                (_, channel) => channel,
+            };
+
+            // reply before leaving in case it's the current channel
+            origin.send(&format!("leaving {channel} on irc")).await?;
+            ctx.db.remove_irc_channel(channel).await?;
+            sender.send_part(channel)?;
+
+            info!(channel, "Left irc channel");
+        }
+        Platform::Matrix => {
+            let Some(client) = ctx.platforms.matrix.get() else {
+                return Ok(Reply::text("matrix is not connected"));
+            };
+
+            let room_id: OwnedRoomId = match (origin.platform(), channel) {
+                (Platform::Matrix, "") => <&RoomId>::try_from(origin.channel())?.to_owned(),
+                (_, "") => return Ok(Reply::text(ctx.usage(&origin, PART_USAGE).await)),
+                (_, channel) => {
+                    if let Ok(alias) = <&RoomAliasId>::try_from(channel) {
+                        client.resolve_room_alias(alias).await?.room_id
+                    } else if let Ok(room_id) = <&RoomId>::try_from(channel) {
+                        room_id.to_owned()
+                    } else {
+                        return Ok(Reply::text("invalid room id or alias"));
+                    }
+                }
+            };
+
+            let Some(room) = client.get_joined_room(&room_id) else {
+                return Ok(Reply::text("not in that room"));
+            };
+
+            origin.send(&format!("leaving {room_id} on matrix")).await?;
+            room.leave().await?;
+
+            info!(%room_id, "Left matrix room");
+        }
+    }
+
+    Ok(Reply::None)
+}
+
+/// `!reload`
+///
+/// Re-reads the `.env` file and the database, then joins or leaves irc channels
+/// that were added to or removed from the database.
+#[command(permission = "owner")]
+async fn reload(ctx: Arc<Context>, _origin: CommandOrigin<'_>, _args: Args<'_>) -> Result<Reply> {
+    if let Err(err) = dotenvy::dotenv_override() {
+        warn!(?err, "Failed to reload .env file");
+    }
+
+    let owners = owners_from_env();
+    let owner_count = owners.len();
+    *ctx.owners.write().unwrap() = owners;
+
+    let prev_channels = ctx.db.irc_channels().await;
+    ctx.db.reload().await?;
+    let channels = ctx.db.irc_channels().await;
+
+    if let Some(sender) = ctx.platforms.irc.get() {
+        for channel in channels.iter().filter(|c| !prev_channels.contains(c)) {
+            sender.send_join(channel)?;
+        }
+
+        for channel in prev_channels.iter().filter(|c| !channels.contains(c)) {
+            sender.send_part(channel)?;
+        }
+    }
+
+    info!(owner_count, channels = channels.len(), "Reloaded config");
+
+    let response = format!(
+        "reloaded config: {owner_count} owner(s), {channel_count} irc channel(s)",
+        channel_count = channels.len()
+    );
+
+    Ok(Reply::text(response))
+}
+
+/// `!status`
+#[command(permission = "owner")]
+async fn status(ctx: Arc<Context>, _origin: CommandOrigin<'_>, _args: Args<'_>) -> Result<Reply> {
+    let uptime = format_duration(ctx.started_at.elapsed());
+
+    let platforms = ctx
+        .platforms
+        .connected()
+        .iter()
+        .map(Platform::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let counts = ctx.stats.counts();
+    let total: u64 = counts.iter().map(|(_, count)| count).sum();
+
+    let top = counts
+        .iter()
+        .take(5)
+        .map(|(name, count)| format!("{name} {count}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let sizes = ctx.cache.sizes();
+
+    let response = format!(
+        "uptime {uptime} | connected: {platforms} | {total} command(s) ({top}) | \
+        cached: {users} users, {maps} maps",
+        users = sizes.users,
+        maps = sizes.maps,
+    );
+
+    Ok(Reply::text(response))
+}
+
+/// Split off a leading `irc` or `matrix`, defaulting to the platform of the origin.
+fn split_platform<'m>(origin: &CommandOrigin<'_>, msg: &'m str) -> (Platform, &'m str) {
+    let msg = msg.trim();
+    let (first, rest) = msg.split_once(' ').unwrap_or((msg, ""));
+
+    match first {
+        "irc" => (Platform::Irc, rest.trim()),
+        "matrix" => (Platform::Matrix, rest.trim()),
+        _ => (origin.platform(), msg),
+    }
+}
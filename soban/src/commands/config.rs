@@ -0,0 +1,379 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use eyre::Result;
+use soban_macros::command;
+
+use crate::{listener::listener, task::task, Args, CommandOrigin, Commands, Context, Reply};
+
+/// Show the settings of the current channel.
+///
+/// Settings are changed through the subcommands, e.g. `!config prefix ?`.
+#[command(permission = "admin")]
+async fn config(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let settings = ctx.db.channel_settings(&origin.channel_key()).await;
+    let prefix = ctx.prefix(&origin, &settings).await;
+
+    // anything but a subcommand
+    if !args.msg.is_empty() {
+        let usage = Commands::get()
+            .entry("config")
+            .map(|entry| entry.usage(&prefix))
+            .unwrap_or_default();
+
+        return Ok(Reply::text(usage));
+    }
+
+    let list = |names: &BTreeSet<String>| {
+        if names.is_empty() {
+            "-".to_owned()
+        } else {
+            names.iter().cloned().collect::<Vec<_>>().join(", ")
+        }
+    };
+
+    let response = format!(
+        "prefix: `{prefix}` | allowed: {allowed} | disabled: {denied} | subscribed: {subscriptions} \
+        | suggestions: {suggestions}",
+        allowed = list(&settings.allowed),
+        denied = list(&settings.denied),
+        subscriptions = list(&settings.subscriptions),
+        suggestions = if settings.no_suggestions { "off" } else { "on" },
+    );
+
+    Ok(Reply::text(response))
+}
+
+/// Set the prefix of the current channel, `reset` to use the platform's prefix.
+#[command(group = "config", name = "prefix", permission = "admin")]
+async fn config_prefix(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    set_prefix(&ctx, &origin, args.msg, PrefixScope::Channel).await
+}
+
+/// Set the default prefix of the current platform, `reset` to use `!`.
+#[command(group = "config", name = "platformprefix", permission = "admin")]
+async fn config_platformprefix(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    set_prefix(&ctx, &origin, args.msg, PrefixScope::Platform).await
+}
+
+/// Allow a command in the current channel again.
+#[command(group = "config", name = "enable", permission = "admin")]
+async fn config_enable(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_list(&ctx, &origin, args.msg, ListChange::Enable).await
+}
+
+/// Forbid a command in the current channel.
+#[command(group = "config", name = "disable", permission = "admin")]
+async fn config_disable(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_list(&ctx, &origin, args.msg, ListChange::Disable).await
+}
+
+/// Add a command to the allow list, if the list isn't empty only its commands can be used.
+#[command(group = "config", name = "allow", permission = "admin")]
+async fn config_allow(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_list(&ctx, &origin, args.msg, ListChange::Allow).await
+}
+
+/// Remove a command from the allow list.
+#[command(group = "config", name = "unallow", permission = "admin")]
+async fn config_unallow(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_list(&ctx, &origin, args.msg, ListChange::Unallow).await
+}
+
+/// Post the messages of a background task in the current channel.
+#[command(group = "config", name = "subscribe", permission = "admin")]
+async fn config_subscribe(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_subscription(&ctx, &origin, args.msg, true).await
+}
+
+/// Stop posting the messages of a background task in the current channel.
+#[command(group = "config", name = "unsubscribe", permission = "admin")]
+async fn config_unsubscribe(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    update_subscription(&ctx, &origin, args.msg, false).await
+}
+
+/// Turn suggestions for mistyped command names `on` or `off`.
+#[command(group = "config", name = "suggestions", permission = "admin")]
+async fn config_suggestions(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    let enabled = match args.msg.trim() {
+        "on" => true,
+        "off" => false,
+        _ => {
+            let usage = ctx.usage(&origin, "config suggestions <on|off>").await;
+
+            return Ok(Reply::text(usage));
+        }
+    };
+
+    ctx.db
+        .update_channel_settings(&origin.channel_key(), |settings| {
+            settings.no_suggestions = !enabled
+        })
+        .await?;
+
+    let response = if enabled {
+        "turned suggestions on"
+    } else {
+        "turned suggestions off"
+    };
+
+    Ok(Reply::text(response))
+}
+
+#[derive(Copy, Clone)]
+enum PrefixScope {
+    Channel,
+    Platform,
+}
+
+async fn set_prefix(
+    ctx: &Context,
+    origin: &CommandOrigin<'_>,
+    value: &str,
+    scope: PrefixScope,
+) -> Result<Reply> {
+    let setting = match scope {
+        PrefixScope::Channel => "prefix",
+        PrefixScope::Platform => "platformprefix",
+    };
+
+    let Some(value) = value.split_whitespace().next() else {
+        let usage = ctx
+            .usage(origin, &format!("config {setting} <prefix|reset>"))
+            .await;
+
+        return Ok(Reply::text(usage));
+    };
+
+    let prefix = (value != "reset").then_some(value);
+
+    if prefix.is_some_and(|prefix| prefix.chars().count() > 5) {
+        return Ok(Reply::text("prefixes can be at most 5 characters long"));
+    }
+
+    match scope {
+        PrefixScope::Channel => {
+            let new_prefix = prefix.map(str::to_owned);
+            ctx.db
+                .update_channel_settings(&origin.channel_key(), |settings| {
+                    settings.prefix = new_prefix
+                })
+                .await?;
+        }
+        PrefixScope::Platform => {
+            ctx.db
+                .set_platform_prefix(origin.platform(), prefix)
+                .await?;
+        }
+    }
+
+    let response = match prefix {
+        Some(prefix) => format!("set the {setting} to `{prefix}`"),
+        None => format!("reset the {setting}"),
+    };
+
+    Ok(Reply::text(response))
+}
+
+#[derive(Copy, Clone)]
+enum ListChange {
+    Enable,
+    Disable,
+    Allow,
+    Unallow,
+}
+
+async fn update_list(
+    ctx: &Context,
+    origin: &CommandOrigin<'_>,
+    value: &str,
+    change: ListChange,
+) -> Result<Reply> {
+    let Some(value) = value.split_whitespace().next() else {
+        return Ok(Reply::text("missing command name"));
+    };
+
+    // groups are enabled and disabled as a whole
+    let name = match Commands::get().entry(value) {
+        Some(entry) => entry.name,
+        None => match listener(value) {
+            Some(listener) => listener.name,
+            None => return Ok(Reply::text(format!("unknown command `{value}`"))),
+        },
+    };
+
+    let changed = ctx
+        .db
+        .update_channel_settings(&origin.channel_key(), |settings| match change {
+            ListChange::Enable => settings.denied.remove(name),
+            ListChange::Disable => settings.denied.insert(name.to_owned()),
+            ListChange::Allow => settings.allowed.insert(name.to_owned()),
+            ListChange::Unallow => settings.allowed.remove(name),
+        })
+        .await?;
+
+    let verb = match change {
+        ListChange::Enable => "enabled",
+        ListChange::Disable => "disabled",
+        ListChange::Allow => "added to the allow list:",
+        ListChange::Unallow => "removed from the allow list:",
+    };
+
+    let response = if changed {
+        format!("{verb} `{name}`")
+    } else {
+        format!("nothing changed for `{name}`")
+    };
+
+    Ok(Reply::text(response))
+}
+
+async fn update_subscription(
+    ctx: &Context,
+    origin: &CommandOrigin<'_>,
+    value: &str,
+    subscribe: bool,
+) -> Result<Reply> {
+    let Some(value) = value.split_whitespace().next() else {
+        return Ok(Reply::text("missing task name"));
+    };
+
+    let Some(task) = task(value) else {
+        return Ok(Reply::text(format!("unknown task `{value}`")));
+    };
+
+    let name = task.name;
+
+    let changed = ctx
+        .db
+        .update_channel_settings(&origin.channel_key(), |settings| {
+            if subscribe {
+                settings.subscriptions.insert(name.to_owned())
+            } else {
+                settings.subscriptions.remove(name)
+            }
+        })
+        .await?;
+
+    let response = match (changed, subscribe) {
+        (true, true) => format!("subscribed to `{name}`"),
+        (true, false) => format!("unsubscribed from `{name}`"),
+        (false, _) => format!("nothing changed for `{name}`"),
+    };
+
+    Ok(Reply::text(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{MockOrigin, TestContext};
+
+    #[tokio::test]
+    async fn subcommands_change_settings() {
+        let test = TestContext::new().await;
+        let owner = MockOrigin::new("owner");
+
+        test.run(&owner, "!config prefix ?").await.unwrap();
+        assert_eq!(owner.take_single(), "set the prefix to `?`");
+
+        test.run(&owner, "?config disable osu").await.unwrap();
+        assert_eq!(owner.take_single(), "disabled `osu`");
+
+        test.run(&owner, "?config").await.unwrap();
+        assert_eq!(
+            owner.take_single(),
+            "prefix: `?` | allowed: - | disabled: osu | subscribed: - | suggestions: on"
+        );
+    }
+
+    #[tokio::test]
+    async fn subscriptions_name_tasks() {
+        let test = TestContext::new().await;
+        let owner = MockOrigin::new("owner");
+
+        test.run(&owner, "!config subscribe PURGE_EXPIRED")
+            .await
+            .unwrap();
+        assert_eq!(owner.take_single(), "subscribed to `purge_expired`");
+
+        test.run(&owner, "!config subscribe nonsense")
+            .await
+            .unwrap();
+        assert_eq!(owner.take_single(), "unknown task `nonsense`");
+
+        let subscribers = test.ctx.db.subscribers("purge_expired").await;
+        assert_eq!(subscribers, ["irc:#test"]);
+
+        test.run(&owner, "!config unsubscribe purge_expired")
+            .await
+            .unwrap();
+        assert_eq!(owner.take_single(), "unsubscribed from `purge_expired`");
+        assert!(test.ctx.db.subscribers("purge_expired").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_subcommands_show_usage() {
+        let test = TestContext::new().await;
+        let owner = MockOrigin::new("owner");
+
+        test.run(&owner, "!config nonsense").await.unwrap();
+        assert_eq!(
+            owner.take_single(),
+            "usage: !config <allow|disable|enable|platformprefix|prefix|subscribe|suggestions|\
+             unallow|unsubscribe>"
+        );
+
+        test.run(&owner, "!config prefix ?").await.unwrap();
+        owner.take_single();
+
+        test.run(&owner, "?config nonsense").await.unwrap();
+        assert!(owner.take_single().starts_with("usage: ?config <allow|"));
+    }
+
+    #[tokio::test]
+    async fn subcommands_check_permissions() {
+        let test = TestContext::new().await;
+        let user = MockOrigin::new("user");
+
+        test.run(&user, "!config prefix ?").await.unwrap();
+        assert_eq!(
+            user.take_single(),
+            "you don't have permission to use this command"
+        );
+    }
+}
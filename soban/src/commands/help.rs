@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use soban_macros::command;
+
+use crate::{Args, Command, CommandOrigin, Commands, Context, Entry, Reply};
+
+/// `!help [command] [subcommand]`
+///
+/// Lists the commands of the channel or describes a single one.
+#[command(aliases("commands"))]
+async fn help(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let settings = ctx.db.channel_settings(&origin.channel_key()).await;
+    let prefix = ctx.prefix(&origin, &settings).await;
+    let commands = Commands::get();
+
+    let mut words = args.msg.split_whitespace();
+
+    let Some(name) = words.next() else {
+        let mut names: Vec<_> = commands
+            .entries
+            .iter()
+            .map(|entry| entry.name)
+            .filter(|name| settings.is_enabled(name) || *name == "config")
+            .collect();
+
+        names.sort_unstable();
+
+        let response = format!(
+            "commands: {} | {prefix}help <command> for details",
+            names.join(", ")
+        );
+
+        return Ok(Reply::text(response));
+    };
+
+    let Some(entry) = commands.entry(name) else {
+        return Ok(Reply::text(format!("unknown command `{name}`")));
+    };
+
+    let response = match words.next() {
+        Some(sub_name) => match entry.subcommand(sub_name) {
+            Some(cmd) => describe_command(cmd, &prefix),
+            None => format!("unknown subcommand `{sub_name}`, {}", entry.usage(&prefix)),
+        },
+        None => describe_entry(entry, &prefix),
+    };
+
+    Ok(Reply::text(response))
+}
+
+fn describe_command(cmd: &Command, prefix: &str) -> String {
+    let mut description = format!("{prefix}{}", cmd.qualified_name);
+
+    if !cmd.aliases.is_empty() {
+        description.push_str(&format!(" (aliases: {})", cmd.aliases.join(", ")));
+    }
+
+    if cmd.description.is_empty() {
+        description.push_str(": no description");
+    } else {
+        // the docs show usages with the default prefix
+        let text = cmd.description.replace("`!", &format!("`{prefix}"));
+
+        description.push_str(": ");
+        description.push_str(&text);
+    }
+
+    description
+}
+
+fn describe_entry(entry: &Entry, prefix: &str) -> String {
+    let mut description = match entry.command {
+        Some(cmd) => describe_command(cmd, prefix),
+        None => format!("{prefix}{}", entry.name),
+    };
+
+    if !entry.subcommands.is_empty() {
+        let names = entry.subcommand_list().join(", ");
+        description.push_str(&format!(" | subcommands: {names}"));
+    }
+
+    description
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{MockOrigin, TestContext};
+
+    #[tokio::test]
+    async fn lists_enabled_commands() {
+        let test = TestContext::new().await;
+
+        test.ctx
+            .db
+            .update_channel_settings("irc:#test", |settings| {
+                settings.denied.insert("ping".to_owned())
+            })
+            .await
+            .unwrap();
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!help").await.unwrap();
+        let response = origin.take_single();
+
+        assert!(response.starts_with("commands: "), "{response}");
+        assert!(response.contains("config, "));
+        assert!(response.contains("recent, "));
+        assert!(!response.contains("ping"));
+        assert!(response.ends_with("| !help <command> for details"));
+    }
+
+    #[tokio::test]
+    async fn describes_commands_and_groups() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!help rs").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "!recent (aliases: rs): `!recent[n] <user> [-v]`, the user's latest or `n`th latest score, \
+             including fails"
+        );
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!help config").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "!config: Show the settings of the current channel. | subcommands: allow, disable, \
+             enable, platformprefix, prefix, subscribe, suggestions, unallow, unsubscribe"
+        );
+
+        let origin = MockOrigin::new("third");
+        test.run(&origin, "!help config prefix").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "!config prefix: Set the prefix of the current channel, `reset` to use the \
+             platform's prefix."
+        );
+    }
+
+    #[tokio::test]
+    async fn usages_use_the_channel_prefix() {
+        let test = TestContext::new().await;
+
+        let owner = MockOrigin::new("owner");
+        test.run(&owner, "!config prefix ?").await.unwrap();
+        owner.take_single();
+
+        test.run(&owner, "?help recent").await.unwrap();
+        assert_eq!(
+            owner.take_single(),
+            "?recent (aliases: rs): `?recent[n] <user> [-v]`, the user's latest or `n`th latest score, \
+             including fails"
+        );
+    }
+}
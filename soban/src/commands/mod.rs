@@ -1,2 +1,7 @@
+mod admin;
+mod config;
+mod help;
 mod osu;
 mod ping;
+mod simulate;
+mod top;
@@ -5,47 +5,387 @@ use eyre::Result;
 use soban_macros::command;
 
 use crate::{
-    utils::osu::{handle_osu, handle_recent, parse_user_id, require_user_id, RecentArgs},
-    Args, CommandOrigin, Context,
+    tokenizer::tokenize,
+    utils::{
+        osu::{
+            handle_osu, handle_recent, handle_recent_list, require_user_id, RecentArgs,
+            ScoreDetails, UserArg, UserArgs,
+        },
+        recent::RecentFilter,
+    },
+    Args, CommandOrigin, Context, Reply,
 };
 
-#[command]
-async fn osu(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
-    let Some(user_id) = parse_user_id(args.msg) else {
-        return require_user_id(origin).await;
-    };
+/// `!osu <user> [--fresh]`, the user's pp, rank and ranked score
+///
+/// `--fresh` skips the cache.
+#[command(cooldown = "3s")]
+async fn osu(ctx: Arc<Context>, _origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let user_args = UserArgs::parse(args.msg, &["--fresh"])?;
+    let fresh = user_args.has("--fresh");
 
-    handle_osu(ctx, origin, user_id).await?;
+    let Some(user) = user_args.user else {
+        return require_user_id();
+    };
 
-    Ok(())
+    handle_osu(ctx, user, fresh).await
 }
 
-#[command(aliases("rs"))]
-async fn recent(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
-    let Some(user) = parse_user_id(args.msg) else {
-        return require_user_id(origin).await;
+/// `!recent[n] <user> [-v]`, the user's latest or `n`th latest score, including
+/// fails
+///
+/// `-v` adds how the pp and stars are made up.
+#[command(aliases("rs"), cooldown = "3s")]
+async fn recent(ctx: Arc<Context>, _origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let user_args = UserArgs::parse(args.msg, VERBOSE_FLAGS)?;
+    let details = verbosity(&user_args);
+
+    let Some(user) = user_args.user else {
+        return require_user_id();
     };
     let recent_args = RecentArgs {
         user,
         idx: args.num,
         include_fails: true,
+        details,
     };
-    handle_recent(ctx, origin, recent_args).await?;
-
-    Ok(())
+    handle_recent(ctx, recent_args).await
 }
 
-#[command(aliases("rp"))]
-async fn recentpass(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<()> {
-    let Some(user) = parse_user_id(args.msg) else {
-        return require_user_id(origin).await;
+/// `!recentpass[n] <user> [-v]`, the user's latest or `n`th latest passed score
+#[command(aliases("rp"), cooldown = "3s")]
+async fn recentpass(
+    ctx: Arc<Context>,
+    _origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    let user_args = UserArgs::parse(args.msg, VERBOSE_FLAGS)?;
+    let details = verbosity(&user_args);
+
+    let Some(user) = user_args.user else {
+        return require_user_id();
     };
     let recent_args = RecentArgs {
         user,
         idx: args.num,
         include_fails: false,
+        details,
     };
-    handle_recent(ctx, origin, recent_args).await?;
+    handle_recent(ctx, recent_args).await
+}
+
+/// `!pp[n] <user>`, how the pp and stars of the user's latest or `n`th latest
+/// score are made up
+#[command(cooldown = "3s")]
+async fn pp(ctx: Arc<Context>, _origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let Some(user) = UserArgs::parse(args.msg, &[])?.user else {
+        return require_user_id();
+    };
+    let recent_args = RecentArgs {
+        user,
+        idx: args.num,
+        include_fails: true,
+        details: ScoreDetails::Breakdown,
+    };
+    handle_recent(ctx, recent_args).await
+}
+
+/// `!recentlist[page] <user> [filters]`, the user's latest scores with repeated
+/// tries of a map grouped together
+///
+/// Filters are `--passes`, mods like `+HD`, minimum stars like `stars=5.5` and
+/// words of the map's name like `map=freedom`.
+#[command(aliases("rsl"), cooldown = "5s")]
+async fn recentlist(
+    ctx: Arc<Context>,
+    _origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    let (filter, tokens) = RecentFilter::parse(tokenize(args.msg))?;
+
+    let Some(user) = UserArg::parse(&tokens)? else {
+        return require_user_id();
+    };
+
+    handle_recent_list(ctx, user, filter, args.num.unwrap_or(1)).await
+}
+
+const VERBOSE_FLAGS: &[&str] = &["-v", "--verbose"];
+
+fn verbosity(user_args: &UserArgs) -> ScoreDetails {
+    if VERBOSE_FLAGS.iter().any(|flag| user_args.has(flag)) {
+        ScoreDetails::Verbose
+    } else {
+        ScoreDetails::Summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{fixture, fixture_json, minutes_ago, MockOrigin, TestContext},
+        Reply,
+    };
+
+    #[tokio::test]
+    async fn osu_user() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!osu peppy").await.unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "peppy - 7654.32pp (#1234) (AU#42)\nRanked Score: 123456789"
+        );
+
+        let replies = origin.take_replies();
+
+        let [Reply::Embed(embed)] = replies.as_slice() else {
+            panic!("expected a single embed");
+        };
+
+        assert_eq!(embed.url.as_deref(), Some("https://osu.ppy.sh/users/2"));
+    }
+
+    #[tokio::test]
+    async fn osu_quoted_user_from_mobile_client() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "\u{3000}!Osu\u{3000} “peppy”  --Fresh")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "peppy - 7654.32pp (#1234) (AU#42)\nRanked Score: 123456789"
+        );
+    }
+
+    #[tokio::test]
+    async fn osu_numbers_are_tried_as_name_then_id() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!osu 2").await.unwrap();
+        assert!(origin.take_single().starts_with("peppy - "));
+
+        let requests = test.stand_in.requests();
+        assert_eq!(
+            requests[..2],
+            ["/api/v2/users/2?key=username", "/api/v2/users/2?key=id"]
+        );
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!osu 3").await.unwrap();
+        assert_eq!(origin.take_single(), "couldn't find user");
+    }
+
+    #[tokio::test]
+    async fn osu_errors() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!osu").await.unwrap();
+        assert_eq!(origin.take_single(), "missing username");
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!osu unknown").await.unwrap();
+        assert_eq!(origin.take_single(), "couldn't find user");
+        test.stand_in
+            .route("/api/v2/users/broken?key=username", 500, "{}");
+
+        let origin = MockOrigin::new("third");
+        test.run(&origin, "!osu broken").await.unwrap();
+        let response = origin.take_single();
+        assert!(
+            response.starts_with("couldn't reach osu!api, try again later (error "),
+            "{response}"
+        );
+    }
+
+    #[tokio::test]
+    async fn osu_is_cached_unless_fresh() {
+        let test = TestContext::new().await;
+        let user_requests = || {
+            test.stand_in
+                .requests()
+                .iter()
+                .filter(|path| path.ends_with("/users/peppy?key=username"))
+                .count()
+        };
+
+        for user in ["a", "b"] {
+            test.run(&MockOrigin::new(user), "!osu peppy")
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(user_requests(), 1);
+
+        test.run(&MockOrigin::new("c"), "!osu --fresh peppy")
+            .await
+            .unwrap();
+
+        assert_eq!(user_requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn recent_score() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rs peppy").await.unwrap();
+        assert!(origin.take_single().contains(" A +HD 97.36% 2m "));
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!rs2 peppy").await.unwrap();
+        assert!(origin.take_single().contains(" X +NM 100.00% FC "));
+
+        let origin = MockOrigin::new("third");
+        test.run(&origin, "!rs3 peppy").await.unwrap();
+        assert_eq!(origin.take_single(), "no recent scores found");
+    }
+
+    #[tokio::test]
+    async fn recent_score_verbose() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rs peppy -v").await.unwrap();
+
+        let response = origin.take_single();
+        let lines: Vec<_> = response.lines().collect();
+
+        assert_eq!(lines.len(), 4, "{response}");
+        assert!(lines[0].contains(" A +HD 97.36% 2m "));
+        assert_eq!(
+            lines[1],
+            "pp: 80.50pp live, 82.66pp recalculated (+2.16) | \
+             aim 60.06 | speed 12.00 | acc 4.66 | fl 0.00"
+        );
+        assert_eq!(
+            lines[2],
+            "stars: ★5.62 (aim 3.13 | speed 1.94 | fl 1.01) | combo 30/54x"
+        );
+        assert_eq!(
+            lines[3],
+            "sliders: 6 slider ends, aim 3.11 without them (slider factor 0.99)"
+        );
+    }
+
+    #[tokio::test]
+    async fn recent_list() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rsl peppy").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "Recent scores of peppy\n\
+             1. [Test]: 2 tries, best X +NM 100.00% FC ★5.62 120.00pp - 5 minutes ago\n\
+             page 1/1"
+        );
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!rsl +HD -p peppy").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "Recent scores of peppy\n\
+             1. [Test]: A +HD 97.36% 2m ★5.62 80.50pp - 5 minutes ago\n\
+             page 1/1"
+        );
+
+        let origin = MockOrigin::new("third");
+        test.run(&origin, "!rsl peppy stars=6").await.unwrap();
+        assert_eq!(origin.take_single(), "no recent scores found");
+
+        let origin = MockOrigin::new("fourth");
+        test.run(&origin, "!rsl2 peppy").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "there is only 1 page of recent scores"
+        );
+    }
+
+    #[tokio::test]
+    async fn recent_list_pages() {
+        let test = TestContext::new().await;
+
+        // a score on each of 12 copies of the fixture map
+        let mut score = fixture_json("recent_scores.json")[0].clone();
+        score["ended_at"] = minutes_ago(5).into();
+
+        let scores: Vec<_> = (1..=12)
+            .map(|map_id| {
+                let mut score = score.clone();
+                score["beatmap_id"] = map_id.into();
+
+                test.stand_in.api(
+                    &format!("beatmaps/lookup?id={map_id}"),
+                    &fixture_json("beatmap.json"),
+                );
+                test.stand_in
+                    .route(&format!("/osu/{map_id}"), 200, fixture("map.osu"));
+
+                score
+            })
+            .collect();
+
+        test.stand_in.api(
+            "users/2/scores/recent?limit=50&offset=0&include_fails=1",
+            &scores.into(),
+        );
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rsl2 peppy").await.unwrap();
+
+        let response = origin.take_single();
+        let lines: Vec<_> = response.lines().collect();
+
+        assert_eq!(lines.len(), 4, "{response}");
+        assert!(lines[1].starts_with("11. [Test]: A +HD"), "{response}");
+        assert_eq!(lines[3], "page 2/2");
+
+        let downloads = || {
+            test.stand_in
+                .requests()
+                .into_iter()
+                .filter(|path| path.starts_with("/osu/"))
+                .collect::<Vec<_>>()
+        };
+
+        // only the listed maps are needed without a minimum of stars
+        assert_eq!(downloads(), ["/osu/11", "/osu/12"]);
+
+        let origin = MockOrigin::new("third");
+        test.run(&origin, "!rsl2 peppy stars=1").await.unwrap();
+        assert!(origin.take_single().ends_with("page 2/2"));
+        assert_eq!(downloads().len(), 12);
+
+        let origin = MockOrigin::new("fourth");
+        test.run(&origin, "!rsl3 peppy").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "there are only 2 pages of recent scores"
+        );
+    }
+
+    #[tokio::test]
+    async fn pp_breakdown() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!pp2 peppy").await.unwrap();
 
-    Ok(())
+        assert_eq!(
+            origin.take_single(),
+            "[Test] +NM\n\
+             pp: 120.00pp live, 182.54pp recalculated (+62.54) | \
+             aim 114.64 | speed 26.96 | acc 31.42 | fl 0.00\n\
+             stars: ★5.62 (aim 3.13 | speed 1.94 | fl 0.80) | combo 54/54x\n\
+             sliders: 6 slider ends, aim 3.11 without them (slider factor 0.99)"
+        );
+    }
 }
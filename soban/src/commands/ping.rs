@@ -3,11 +3,10 @@ use std::sync::Arc;
 use eyre::Result;
 use soban_macros::command;
 
-use crate::{Args, CommandOrigin, Context};
+use crate::{Args, CommandOrigin, Context, Reply};
 
+/// `!ping`, check whether the bot is responding
 #[command(aliases("p"))]
-async fn ping(_ctx: Arc<Context>, origin: CommandOrigin<'_>, _args: Args<'_>) -> Result<()> {
-    origin.send("pong!").await?;
-
-    Ok(())
+async fn ping(_ctx: Arc<Context>, _origin: CommandOrigin<'_>, _args: Args<'_>) -> Result<Reply> {
+    Ok(Reply::text("pong!"))
 }
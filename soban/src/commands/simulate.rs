@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use eyre::Result;
+
+use soban_macros::command;
+
+use crate::{
+    tokenizer::tokenize,
+    utils::{calc::ScoreParams, osu::handle_simulate},
+    Args, CommandOrigin, Context, Reply,
+};
+
+/// `!simulate <map> [+mods] [acc%] [combo x] [misses m] [n x100] [n x50] [rate=1.2]`,
+/// stars and pp of a made-up score on any map
+///
+/// Anything that's left out is assumed to be perfect, e.g.
+/// `!sim 456 +DT 99.1% 1200x 2m 5x100`.
+#[command(aliases("sim"), cooldown = "3s")]
+async fn simulate(ctx: Arc<Context>, _origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let (map_id, params) = ScoreParams::parse_simulation(&tokenize(args.msg))?;
+
+    handle_simulate(ctx, map_id, params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{MockOrigin, TestContext};
+
+    #[tokio::test]
+    async fn simulates_scores() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!sim https://osu.ppy.sh/b/1 +HD 2m 2x100")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "[Test] +HD 2x100 2m\n\
+             Map: CS4 AR9 OD8 HP5 | 200 BPM | 0:10\n\
+             Stars: ★5.62 (aim 3.13, speed 1.94) | max combo 54x\n\
+             pp: 130.89pp (aim 96.99 | speed 19.20 | acc 4.66 | fl 0.00)\n\
+             Max pp: 203.23pp"
+        );
+    }
+
+    #[tokio::test]
+    async fn simulates_mods_and_clock_rates() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!sim 1 +HRDT rate=1.2").await.unwrap();
+
+        let response = origin.take_single();
+        let map = response.lines().nth(1).unwrap();
+
+        assert_eq!(map, "Map: CS5.2 AR10.5 OD10.56 HP7 | 240 BPM | 0:08");
+    }
+
+    #[tokio::test]
+    async fn simulate_errors() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("a");
+        test.run(&origin, "!sim 1 420%").await.unwrap();
+        assert_eq!(origin.take_single(), "couldn't understand `420%`");
+
+        let origin = MockOrigin::new("b");
+        test.run(&origin, "!sim +HD").await.unwrap();
+        assert_eq!(origin.take_single(), "missing map id or link");
+
+        let origin = MockOrigin::new("c");
+        test.run(&origin, "!sim 404").await.unwrap();
+        assert_eq!(origin.take_single(), "couldn't find that on osu!");
+    }
+}
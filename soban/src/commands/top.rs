@@ -0,0 +1,287 @@
+use std::sync::Arc;
+
+use eyre::Result;
+
+use soban_macros::command;
+
+use crate::{
+    tokenizer::tokenize,
+    utils::{
+        osu::{
+            handle_nochoke, handle_rank, handle_recent_best, handle_whatif, require_user_id,
+            RankTarget, UserArg, UserArgs,
+        },
+        pp::parse_pp,
+        rank::Rank,
+    },
+    Args, CommandError, CommandOrigin, Context, Reply,
+};
+
+/// Newest top plays that are shown by default and at most
+const RECENT_BEST_DEFAULT: usize = 5;
+const RECENT_BEST_MAX: usize = 25;
+
+/// `!nochoke <user>`, the user's total pp if all of their top plays were full combos
+///
+/// Misses are counted as 300s while 100s and 50s are kept.
+#[command(aliases("nc"), cooldown = "10s")]
+async fn nochoke(ctx: Arc<Context>, _origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let Some(user) = UserArgs::parse(args.msg, &[])?.user else {
+        return require_user_id();
+    };
+
+    handle_nochoke(ctx, user).await
+}
+
+/// `!recentbest <user> [amount]`, the user's newest top plays with what they add
+/// to the user's total pp
+#[command(aliases("rb"), cooldown = "5s")]
+async fn recentbest(
+    ctx: Arc<Context>,
+    _origin: CommandOrigin<'_>,
+    args: Args<'_>,
+) -> Result<Reply> {
+    let mut tokens = tokenize(args.msg);
+
+    // a lone number is a user, not the amount
+    let amount = match tokens.last() {
+        Some(token) if !token.quoted && tokens.len() > 1 => token.text.parse().ok(),
+        _ => None,
+    };
+
+    if amount.is_some() {
+        tokens.pop();
+    }
+
+    let Some(user) = UserArg::parse(&tokens)? else {
+        return require_user_id();
+    };
+
+    let amount = amount
+        .unwrap_or(RECENT_BEST_DEFAULT)
+        .clamp(1, RECENT_BEST_MAX);
+
+    handle_recent_best(ctx, user, amount).await
+}
+
+/// `!whatif <user> <pp>`, the user's total pp and rank if they got a play worth `pp`
+#[command(cooldown = "5s")]
+async fn whatif(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let mut tokens = tokenize(args.msg);
+
+    let pp = tokens
+        .pop()
+        .filter(|token| !token.quoted)
+        .and_then(|token| parse_pp(&token.text));
+
+    let (Some(pp), Some(user)) = (pp, UserArg::parse(&tokens)?) else {
+        let usage = ctx.usage(&origin, "whatif <user> <pp>").await;
+
+        return Err(CommandError::user(usage).into());
+    };
+
+    handle_whatif(ctx, user, pp).await
+}
+
+/// `!rank <user> <#rank|XX#rank|pp>`, the pp the user needs for a global or
+/// country rank or a total amount of pp
+///
+/// Also shows the single new top play that would get them there.
+#[command(cooldown = "5s")]
+async fn rank(ctx: Arc<Context>, origin: CommandOrigin<'_>, args: Args<'_>) -> Result<Reply> {
+    let mut tokens = tokenize(args.msg);
+
+    let target = tokens
+        .pop()
+        .filter(|token| !token.quoted)
+        .and_then(|token| match Rank::parse(&token.text) {
+            Some(rank) => Some(RankTarget::Rank(rank)),
+            None => parse_pp(&token.text).map(RankTarget::Pp),
+        });
+
+    let (Some(target), Some(user)) = (target, UserArg::parse(&tokens)?) else {
+        let usage = ctx.usage(&origin, "rank <user> <#rank|XX#rank|pp>").await;
+
+        return Err(CommandError::user(usage).into());
+    };
+
+    handle_rank(ctx, user, target).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{fixture_json, minutes_ago, ranking_page, MockOrigin, TestContext};
+
+    #[tokio::test]
+    async fn nochoke_unchokes_top_plays() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!nochoke peppy").await.unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "peppy - 7654.32pp → 7751.17pp (+96.85pp) if unchoked\n\
+             Unchoked: 1 of 2 scores\n\
+             Rank: #1234 → #2\n\
+             Biggest unchoke: #2 https://osu.ppy.sh/b/1 +HD 80.50pp → 179.32pp"
+        );
+    }
+
+    #[tokio::test]
+    async fn whatif_inserts_a_play() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!whatif peppy 150pp").await.unwrap();
+
+        // 150 + 120 * 0.95 + 80.5 * 0.95^2 replace 120 + 80.5 * 0.95
+        assert_eq!(
+            origin.take_single(),
+            "What if peppy got a 150.00pp play?\n\
+             Top play: #1\n\
+             Total pp: 7654.32pp → 7794.50pp (+140.18pp)\n\
+             Rank: #1234 → #2"
+        );
+    }
+
+    #[tokio::test]
+    async fn recent_best_sorts_by_date() {
+        let test = TestContext::new().await;
+
+        let mut scores = fixture_json("best_scores.json");
+        scores[0]["ended_at"] = minutes_ago(60).into();
+        scores[1]["ended_at"] = minutes_ago(5).into();
+        test.stand_in.api("users/2/scores/best?limit=100", &scores);
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rb peppy").await.unwrap();
+
+        // without the 120pp play the 80.5pp one would move up, so it adds less
+        assert_eq!(
+            origin.take_single(),
+            "Newest top plays of peppy\n\
+             #2 [Test]: +HD 97.36% 80.50pp, 76.47pp weighted, +76.47pp total - 5 minutes ago\n\
+             #1 [Test]: +NM 100.00% 120.00pp, 120.00pp weighted, +115.97pp total - 1 hour ago"
+        );
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!rb peppy 1").await.unwrap();
+        assert!(origin.take_single().ends_with("- 5 minutes ago"));
+    }
+
+    #[tokio::test]
+    async fn whatif_usage() {
+        let test = TestContext::new().await;
+
+        for (user, msg) in [
+            ("a", "!whatif peppy"),
+            ("b", "!whatif 300"),
+            ("c", "!whatif"),
+        ] {
+            let origin = MockOrigin::new(user);
+            test.run(&origin, msg).await.unwrap();
+
+            assert_eq!(origin.take_single(), "usage: !whatif <user> <pp>", "{msg}");
+        }
+
+        let owner = MockOrigin::new("owner");
+        test.run(&owner, "!config prefix ?").await.unwrap();
+        owner.take_single();
+
+        test.run(&owner, "?whatif").await.unwrap();
+        assert_eq!(owner.take_single(), "usage: ?whatif <user> <pp>");
+    }
+
+    #[tokio::test]
+    async fn rank_needed_pp() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!rank peppy #2").await.unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "What peppy needs for #2 (7700.00pp)\n\
+             Missing pp: 7654.32pp → 7700.00pp (+45.68pp)\n\
+             Single play: a 50.62pp play, #3 in their top plays"
+        );
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!rank peppy 7000").await.unwrap();
+
+        assert_eq!(
+            origin.take_single(),
+            "peppy already has 7654.32pp, enough for 7000.00pp"
+        );
+    }
+
+    #[tokio::test]
+    async fn rank_by_country() {
+        let test = TestContext::new().await;
+        test.stand_in.api(
+            "rankings/osu/performance?country=AU&cursor[page]=1",
+            &ranking_page(&[9000.0], 1),
+        );
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rank peppy au#1").await.unwrap();
+        assert!(origin
+            .take_single()
+            .starts_with("What peppy needs for AU#1 (9000.00pp)"));
+
+        let origin = MockOrigin::new("other");
+        test.run(&origin, "!rank peppy au#2").await.unwrap();
+        assert_eq!(origin.take_single(), "there's nobody at AU#2");
+    }
+
+    #[tokio::test]
+    async fn rank_usage() {
+        let test = TestContext::new().await;
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!rank peppy").await.unwrap();
+        assert_eq!(
+            origin.take_single(),
+            "usage: !rank <user> <#rank|XX#rank|pp>"
+        );
+
+        let owner = MockOrigin::new("owner");
+        test.run(&owner, "!config prefix ~").await.unwrap();
+        owner.take_single();
+
+        test.run(&owner, "~rank #1").await.unwrap();
+        assert_eq!(
+            owner.take_single(),
+            "usage: ~rank <user> <#rank|XX#rank|pp>"
+        );
+    }
+
+    #[tokio::test]
+    async fn nochoke_without_top_plays() {
+        let test = TestContext::new().await;
+        test.stand_in
+            .api("users/2/scores/best?limit=100", &serde_json::json!([]));
+
+        let origin = MockOrigin::new("user");
+        test.run(&origin, "!nc peppy").await.unwrap();
+
+        assert_eq!(origin.take_single(), "no top scores found");
+    }
+
+    #[tokio::test]
+    async fn users_without_stats() {
+        let test = TestContext::new().await;
+
+        let mut user = fixture_json("user.json");
+        user.as_object_mut().unwrap().remove("statistics");
+        test.stand_in.api("users/peppy?key=username", &user);
+
+        for cmd in ["!nc peppy", "!whatif peppy 100", "!rank peppy #1"] {
+            let origin = MockOrigin::new("user");
+            test.run(&origin, cmd).await.unwrap();
+
+            assert_eq!(origin.take_single(), "user has no stats", "{cmd}");
+        }
+    }
+}
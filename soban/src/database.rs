@@ -0,0 +1,263 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, sync::RwLock};
+
+use crate::Platform;
+
+/// Persistent bot state, stored as a json file.
+///
+/// Every modification is written to disk right away.
+pub struct Database {
+    path: PathBuf,
+    data: RwLock<Data>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Data {
+    /// Custom roles of users, keyed by `platform:user`
+    roles: HashMap<String, BTreeSet<String>>,
+    /// Irc channels to join on startup, seeded from the config on first start
+    irc_channels: Option<BTreeSet<String>>,
+    /// Default command prefixes, keyed by platform
+    platform_prefixes: HashMap<String, String>,
+    /// Settings of individual channels and rooms, keyed by `platform:channel`
+    channels: HashMap<String, ChannelSettings>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ChannelSettings {
+    /// Overrides the platform's default prefix
+    pub prefix: Option<String>,
+    /// If not empty, only these commands can be used
+    pub allowed: BTreeSet<String>,
+    /// Commands that can't be used
+    pub denied: BTreeSet<String>,
+    /// Tasks that post to the channel
+    pub subscriptions: BTreeSet<String>,
+    /// Don't suggest commands for mistyped command names
+    pub no_suggestions: bool,
+}
+
+impl ChannelSettings {
+    pub fn is_enabled(&self, cmd_name: &str) -> bool {
+        (self.allowed.is_empty() || self.allowed.contains(cmd_name))
+            && !self.denied.contains(cmd_name)
+    }
+
+    fn is_default(&self) -> bool {
+        self.prefix.is_none()
+            && self.allowed.is_empty()
+            && self.denied.is_empty()
+            && self.subscriptions.is_empty()
+            && !self.no_suggestions
+    }
+}
+
+impl Database {
+    /// Load the database from the given path or start with an empty one if the file doesn't exist.
+    pub async fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let data = Self::load(&path).await?;
+
+        Ok(Self {
+            path,
+            data: RwLock::new(data),
+        })
+    }
+
+    async fn load(path: &Path) -> Result<Data> {
+        match fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .wrap_err_with(|| format!("Failed to deserialize database at {path:?}")),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Data::default()),
+            Err(err) => Err(err).wrap_err_with(|| format!("Failed to read database at {path:?}")),
+        }
+    }
+
+    /// Replace the in-memory state with the file's current content.
+    pub async fn reload(&self) -> Result<()> {
+        let data = Self::load(&self.path).await?;
+        *self.data.write().await = data;
+
+        Ok(())
+    }
+
+    async fn store(&self, data: &Data) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(data)?;
+
+        // write to a temporary file first so a crash can't leave a half-written database
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, bytes).await?;
+        fs::rename(&tmp_path, &self.path).await?;
+
+        Ok(())
+    }
+
+    pub async fn roles(&self, user: &str) -> BTreeSet<String> {
+        let data = self.data.read().await;
+
+        data.roles.get(user).cloned().unwrap_or_default()
+    }
+
+    pub async fn has_role(&self, user: &str, role: &str) -> bool {
+        let data = self.data.read().await;
+
+        data.roles
+            .get(user)
+            .is_some_and(|roles| roles.contains(role))
+    }
+
+    /// Returns `false` if the user already had the role.
+    pub async fn add_role(&self, user: &str, role: &str) -> Result<bool> {
+        let mut data = self.data.write().await;
+
+        let added = data
+            .roles
+            .entry(user.to_owned())
+            .or_default()
+            .insert(role.to_owned());
+
+        if added {
+            self.store(&data).await?;
+        }
+
+        Ok(added)
+    }
+
+    /// Returns `false` if the user didn't have the role.
+    pub async fn remove_role(&self, user: &str, role: &str) -> Result<bool> {
+        let mut data = self.data.write().await;
+
+        let Some(roles) = data.roles.get_mut(user) else {
+            return Ok(false);
+        };
+
+        let removed = roles.remove(role);
+
+        if roles.is_empty() {
+            data.roles.remove(user);
+        }
+
+        if removed {
+            self.store(&data).await?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Get the irc channels to join, storing the given defaults if there are none yet.
+    pub async fn irc_channels_or_init(&self, defaults: &[String]) -> Result<Vec<String>> {
+        let mut data = self.data.write().await;
+
+        if data.irc_channels.is_none() {
+            data.irc_channels = Some(defaults.iter().cloned().collect());
+            self.store(&data).await?;
+        }
+
+        Ok(data.irc_channels.iter().flatten().cloned().collect())
+    }
+
+    pub async fn irc_channels(&self) -> Vec<String> {
+        let data = self.data.read().await;
+
+        data.irc_channels.iter().flatten().cloned().collect()
+    }
+
+    /// Returns `false` if the channel was already stored.
+    pub async fn add_irc_channel(&self, channel: &str) -> Result<bool> {
+        let mut data = self.data.write().await;
+
+        let added = data
+            .irc_channels
+            .get_or_insert_with(BTreeSet::new)
+            .insert(channel.to_owned());
+
+        if added {
+            self.store(&data).await?;
+        }
+
+        Ok(added)
+    }
+
+    /// Returns `false` if the channel wasn't stored.
+    pub async fn remove_irc_channel(&self, channel: &str) -> Result<bool> {
+        let mut data = self.data.write().await;
+
+        let removed = data
+            .irc_channels
+            .as_mut()
+            .is_some_and(|channels| channels.remove(channel));
+
+        if removed {
+            self.store(&data).await?;
+        }
+
+        Ok(removed)
+    }
+
+    pub async fn platform_prefix(&self, platform: Platform) -> Option<String> {
+        let data = self.data.read().await;
+
+        data.platform_prefixes.get(&platform.to_string()).cloned()
+    }
+
+    /// Set the default prefix of a platform or remove it if `None`.
+    pub async fn set_platform_prefix(
+        &self,
+        platform: Platform,
+        prefix: Option<&str>,
+    ) -> Result<()> {
+        let mut data = self.data.write().await;
+        let platform = platform.to_string();
+
+        match prefix {
+            Some(prefix) => data.platform_prefixes.insert(platform, prefix.to_owned()),
+            None => data.platform_prefixes.remove(&platform),
+        };
+
+        self.store(&data).await
+    }
+
+    pub async fn channel_settings(&self, channel: &str) -> ChannelSettings {
+        let data = self.data.read().await;
+
+        data.channels.get(channel).cloned().unwrap_or_default()
+    }
+
+    /// Keys of the channels that subscribed to the task.
+    pub async fn subscribers(&self, task: &str) -> Vec<String> {
+        let data = self.data.read().await;
+
+        data.channels
+            .iter()
+            .filter(|(_, settings)| settings.subscriptions.contains(task))
+            .map(|(channel, _)| channel.to_owned())
+            .collect()
+    }
+
+    /// Modify the settings of a channel and store them.
+    pub async fn update_channel_settings<F, T>(&self, channel: &str, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut ChannelSettings) -> T,
+    {
+        let mut data = self.data.write().await;
+        let settings = data.channels.entry(channel.to_owned()).or_default();
+        let res = f(settings);
+
+        if settings.is_default() {
+            data.channels.remove(channel);
+        }
+
+        self.store(&data).await?;
+
+        Ok(res)
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use eyre::Report;
+use rosu_v2::prelude::OsuError;
+
+use crate::{utils::random::random_u64, Reply};
+
+/// Why a command failed, turned into a reply by the dispatcher.
+///
+/// Commands return [`eyre::Result`] so any error can be propagated with `?`.
+/// Errors that aren't a `CommandError` are classified when they reach the
+/// dispatcher, see the `From<Report>` impl.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The user made a mistake, e.g. a missing argument or an unknown username.
+    /// The message is shown as is.
+    User(String),
+    /// A service the bot depends on failed or is ratelimiting requests
+    Upstream {
+        service: &'static str,
+        ratelimited: bool,
+        source: Report,
+    },
+    /// Anything else, most likely a bug
+    Internal(Report),
+}
+
+impl CommandError {
+    pub fn user(msg: impl Into<String>) -> Self {
+        Self::User(msg.into())
+    }
+
+    pub fn upstream(service: &'static str, source: impl Into<Report>) -> Self {
+        Self::Upstream {
+            service,
+            ratelimited: false,
+            source: source.into(),
+        }
+    }
+
+    /// Log the error under a new id and turn it into a reply that mentions the id.
+    pub fn into_reply(self, cmd_name: &str) -> Reply {
+        let id = format!("{:08x}", random_u64() as u32);
+
+        match self {
+            Self::User(msg) => {
+                debug!(name = cmd_name, msg, "Command refused");
+
+                Reply::text(msg)
+            }
+            Self::Upstream {
+                service,
+                ratelimited,
+                source,
+            } => {
+                warn!(
+                    name = cmd_name,
+                    id,
+                    service,
+                    ?source,
+                    "Upstream error in command"
+                );
+
+                if ratelimited {
+                    Reply::text(format!(
+                        "{service} is ratelimiting requests, try again in a bit (error {id})"
+                    ))
+                } else {
+                    Reply::text(format!(
+                        "couldn't reach {service}, try again later (error {id})"
+                    ))
+                }
+            }
+            Self::Internal(source) => {
+                error!(name = cmd_name, id, ?source, "Failed to run command");
+
+                Reply::text(format!("something went wrong (error {id})"))
+            }
+        }
+    }
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::User(msg) => f.write_str(msg),
+            Self::Upstream { service, .. } => write!(f, "failed to reach {service}"),
+            Self::Internal(source) => Display::fmt(source, f),
+        }
+    }
+}
+
+impl StdError for CommandError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::User(_) => None,
+            Self::Upstream { source, .. } | Self::Internal(source) => Some(source.as_ref()),
+        }
+    }
+}
+
+impl From<OsuError> for CommandError {
+    fn from(err: OsuError) -> Self {
+        const SERVICE: &str = "osu!api";
+
+        match err {
+            OsuError::NotFound => Self::user("couldn't find that on osu!"),
+            OsuError::Response { status, .. } if status.as_u16() == 429 => Self::Upstream {
+                service: SERVICE,
+                ratelimited: true,
+                source: err.into(),
+            },
+            OsuError::Response { status, .. } if status.is_server_error() => {
+                Self::upstream(SERVICE, err)
+            }
+            OsuError::BodyError { .. }
+            | OsuError::ChunkingResponse { .. }
+            | OsuError::NoToken
+            | OsuError::Request { .. }
+            | OsuError::RequestTimeout
+            | OsuError::ServiceUnavailable(_)
+            | OsuError::UpdateToken { .. } => Self::upstream(SERVICE, err),
+            err => Self::Internal(err.into()),
+        }
+    }
+}
+
+impl From<Report> for CommandError {
+    fn from(err: Report) -> Self {
+        let err = match err.downcast::<CommandError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
+
+        match err.downcast::<OsuError>() {
+            Ok(err) => err.into(),
+            Err(err) => Self::Internal(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use super::*;
+
+    #[test]
+    fn reports_are_classified() {
+        let err = Report::from(CommandError::user("missing username")).wrap_err("context");
+        assert!(
+            matches!(CommandError::from(err), CommandError::User(msg) if msg == "missing username")
+        );
+
+        let err = Report::from(OsuError::RequestTimeout);
+        assert!(matches!(
+            CommandError::from(err),
+            CommandError::Upstream {
+                ratelimited: false,
+                ..
+            }
+        ));
+
+        let err = Report::from(OsuError::NotFound);
+        assert!(matches!(CommandError::from(err), CommandError::User(_)));
+
+        let err = eyre!("failed to parse map");
+        assert!(matches!(CommandError::from(err), CommandError::Internal(_)));
+    }
+
+    #[test]
+    fn replies_mention_the_error_id() {
+        let Reply::Text(text) = CommandError::user("no scores").into_reply("rs") else {
+            panic!("expected text");
+        };
+        assert_eq!(text, "no scores");
+
+        let Reply::Text(text) = CommandError::Internal(eyre!("oops")).into_reply("rs") else {
+            panic!("expected text");
+        };
+        assert!(text.starts_with("something went wrong (error "), "{text}");
+    }
+}
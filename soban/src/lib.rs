@@ -1,123 +1,873 @@
 #[macro_use]
 extern crate tracing;
 
+mod api;
+mod cache;
 mod commands;
+mod database;
+mod error;
+mod listener;
+mod listeners;
+mod origin;
+mod permissions;
+mod ratelimit;
+mod reply;
+mod stats;
+mod task;
+mod tasks;
+mod tokenizer;
 mod utils;
 
-use eyre::Result;
+#[cfg(test)]
+mod test_support;
+
+pub use crate::{
+    api::OsuApi,
+    cache::{CacheSizes, OsuCache},
+    database::{ChannelSettings, Database},
+    error::CommandError,
+    listener::{handle_event, Event, EventKind, Listener},
+    origin::{CommandOrigin, IrcOrigin, MatrixOrigin, Origin},
+    permissions::{normalize_user_key, owners_from_env, Permission},
+    ratelimit::Ratelimiter,
+    reply::{Attachment, Embed, Reply},
+    stats::CommandStats,
+    task::{spawn_tasks, Channels, Task},
+};
+
+use eyre::{eyre, Result};
 use futures::future::BoxFuture;
 use irc::client::Sender;
 use linkme::distributed_slice;
-use matrix_sdk::{room::Joined, ruma::events::room::message::RoomMessageEventContent};
-use rosu_v2::Osu;
+use matrix_sdk::{
+    ruma::{events::room::message::RoomMessageEventContent, RoomId},
+    Client as MatrixClient,
+};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter, Result as FmtResult},
     iter,
-    sync::{Arc, OnceLock},
+    path::PathBuf,
+    sync::{Arc, OnceLock, RwLock},
+    time::{Duration, Instant},
 };
 
-pub enum CommandOrigin<'a> {
-    Irc {
-        sender: &'a Sender,
-        target: &'a String,
-    },
-    Matrix {
-        room: &'a Joined,
-    },
+use crate::{
+    ratelimit::Ratelimited,
+    tokenizer::{split_command, split_word, strip_mention, strip_prefix},
+    utils::suggest::did_you_mean,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Platform {
+    Irc,
+    Matrix,
 }
 
-impl CommandOrigin<'_> {
-    pub async fn send(&self, msg: &str) -> Result<()> {
+impl Display for Platform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         match self {
-            CommandOrigin::Irc { sender, target } => sender.send_privmsg(target, msg)?,
-            CommandOrigin::Matrix { room } => {
+            Platform::Irc => f.write_str("irc"),
+            Platform::Matrix => f.write_str("matrix"),
+        }
+    }
+}
+
+pub struct Author<'a> {
+    pub platform: Platform,
+    /// Unique identifier on the platform, i.e. the nick on irc and the full user id on matrix
+    pub id: &'a str,
+    /// Display-friendly name
+    pub name: &'a str,
+    /// Account the platform vouches for, i.e. the services account on irc and
+    /// the user id on matrix
+    pub account: Option<&'a str>,
+}
+
+impl Author<'_> {
+    /// Identifier of the form `platform:id`, e.g. for ratelimits.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.platform, self.id)
+    }
+
+    /// Identifier of the form `platform:account` as used for owners and roles.
+    ///
+    /// Returns `None` if the user isn't logged in, since nicks can be taken by anyone.
+    pub fn account_key(&self) -> Option<String> {
+        let key = format!("{}:{}", self.platform, self.account?);
+
+        Some(normalize_user_key(&key))
+    }
+}
+
+pub struct Context {
+    pub osu: Box<dyn OsuApi>,
+    pub cache: OsuCache,
+    pub ratelimiter: Ratelimiter,
+    pub db: Database,
+    /// Account keys of the bot owners, see [`Author::account_key`]
+    pub owners: RwLock<HashSet<String>>,
+    pub platforms: Platforms,
+    pub stats: CommandStats,
+    pub started_at: Instant,
+    /// Directory in which `.osu` files are stored
+    pub map_path: PathBuf,
+}
+
+impl Context {
+    pub fn new(
+        osu: Box<dyn OsuApi>,
+        db: Database,
+        owners: HashSet<String>,
+        map_path: PathBuf,
+    ) -> Self {
+        Self {
+            osu,
+            cache: OsuCache::default(),
+            ratelimiter: Ratelimiter::default(),
+            db,
+            owners: RwLock::new(owners),
+            platforms: Platforms::default(),
+            stats: CommandStats::default(),
+            started_at: Instant::now(),
+            map_path,
+        }
+    }
+
+    pub fn is_owner(&self, user_key: &str) -> bool {
+        self.owners.read().unwrap().contains(user_key)
+    }
+
+    /// Usage of a command like `usage: !whatif <user> <pp>` with the prefix of
+    /// the origin's channel, given without the prefix.
+    pub async fn usage(&self, origin: &CommandOrigin<'_>, usage: &str) -> String {
+        let settings = self.db.channel_settings(&origin.channel_key()).await;
+        let prefix = self.prefix(origin, &settings).await;
+
+        format!("usage: {prefix}{usage}")
+    }
+
+    /// Prefix of the channel with the given settings, falling back to the
+    /// platform's prefix and then [`DEFAULT_PREFIX`].
+    pub async fn prefix(&self, origin: &CommandOrigin<'_>, settings: &ChannelSettings) -> String {
+        match settings.prefix {
+            Some(ref prefix) => prefix.to_owned(),
+            None => self
+                .db
+                .platform_prefix(origin.platform())
+                .await
+                .unwrap_or_else(|| DEFAULT_PREFIX.to_owned()),
+        }
+    }
+}
+
+/// Handles to the running platform sessions, set once each platform is connected.
+#[derive(Default)]
+pub struct Platforms {
+    pub irc: OnceLock<Sender>,
+    pub matrix: OnceLock<MatrixClient>,
+}
+
+impl Platforms {
+    pub fn connected(&self) -> Vec<Platform> {
+        let mut platforms = Vec::new();
+
+        if self.irc.get().is_some() {
+            platforms.push(Platform::Irc);
+        }
+
+        if self.matrix.get().is_some_and(MatrixClient::logged_in) {
+            platforms.push(Platform::Matrix);
+        }
+
+        platforms
+    }
+
+    /// Send a message to a channel outside of a command, e.g. from a task.
+    ///
+    /// The channel is identified by its key of the form `platform:channel`.
+    pub async fn send(&self, channel: &str, msg: &str) -> Result<()> {
+        match channel.split_once(':') {
+            Some(("irc", target)) => {
+                let sender = self
+                    .irc
+                    .get()
+                    .ok_or_else(|| eyre!("irc is not connected"))?;
+                origin::send_irc_lines(sender, target, msg)?;
+            }
+            Some(("matrix", room_id)) => {
+                let client = self
+                    .matrix
+                    .get()
+                    .ok_or_else(|| eyre!("matrix is not connected"))?;
+                let room_id = <&RoomId>::try_from(room_id)?;
+
+                let room = client
+                    .get_joined_room(room_id)
+                    .ok_or_else(|| eyre!("not in room {room_id}"))?;
+
                 room.send(RoomMessageEventContent::text_plain(msg), None)
                     .await?;
             }
+            _ => return Err(eyre!("invalid channel `{channel}`")),
         }
+
         Ok(())
     }
 }
 
-pub struct Context {
-    pub osu: Osu,
-}
-
-type CommandFn = for<'a> fn(Arc<Context>, CommandOrigin<'a>, Args<'a>) -> BoxFuture<'a, Result<()>>;
+type CommandFn =
+    for<'a> fn(Arc<Context>, CommandOrigin<'a>, Args<'a>) -> BoxFuture<'a, Result<Reply>>;
 
 pub struct Command {
     name: &'static str,
+    /// Name of the group if this is a subcommand like `!config prefix`
+    group: Option<&'static str>,
+    /// Name prefixed by the group, identifies the command in stats and cooldowns
+    qualified_name: &'static str,
+    /// First paragraph of the command's doc comments
+    description: &'static str,
     aliases: &'static [&'static str],
+    cooldown: Option<Duration>,
+    permission: Permission,
+    /// `file:line` of the command's definition
+    location: &'static str,
     run: CommandFn,
 }
 
+impl Command {
+    /// Lowercase name and aliases.
+    fn names(&self) -> impl Iterator<Item = String> {
+        iter::once(self.name)
+            .chain(self.aliases.iter().copied())
+            .map(str::to_lowercase)
+    }
+
+    /// Name of the top-level command or group, used to enable or disable it.
+    fn top_level_name(&self) -> &'static str {
+        self.group.unwrap_or(self.name)
+    }
+}
+
 struct Args<'a> {
     msg: &'a str,
     num: Option<u32>,
 }
 
-struct Commands(HashMap<&'static str, CommandFn>);
+/// Registered commands and groups by their lowercase names and aliases.
+struct Commands {
+    entries: Vec<Entry>,
+    /// Index into `entries` for every name and alias
+    names: HashMap<String, usize>,
+}
+
+/// A top-level command, a group of subcommands, or both.
+///
+/// If a group has a command of the same name, that command handles messages
+/// that don't name one of the subcommands.
+struct Entry {
+    name: &'static str,
+    command: Option<&'static Command>,
+    subcommands: Vec<&'static Command>,
+    /// Index into `subcommands` for every name and alias
+    subcommand_names: HashMap<String, usize>,
+}
+
+impl Entry {
+    fn new(name: &'static str, command: Option<&'static Command>) -> Self {
+        Self {
+            name,
+            command,
+            subcommands: Vec::new(),
+            subcommand_names: HashMap::new(),
+        }
+    }
+
+    /// The command itself or the group's first subcommand.
+    fn any_command(&self) -> &'static Command {
+        self.command
+            .or_else(|| self.subcommands.first().copied())
+            .expect("entries have at least one command")
+    }
+
+    fn subcommand(&self, name: &str) -> Option<&'static Command> {
+        let idx = *self.subcommand_names.get(&name.to_lowercase())?;
+
+        Some(self.subcommands[idx])
+    }
+
+    /// Names of the subcommands in alphabetical order.
+    fn subcommand_list(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.subcommands.iter().map(|cmd| cmd.name).collect();
+        names.sort_unstable();
+
+        names
+    }
+
+    /// Usage like `usage: !config <allow|disable|...>` listing the subcommands.
+    fn usage(&self, prefix: &str) -> String {
+        let subcommands = self.subcommand_list().join("|");
+
+        format!("usage: {prefix}{} <{subcommands}>", self.name)
+    }
+}
+
+/// Outcome of looking up the first words of a message.
+#[derive(Clone, Copy)]
+enum Resolved<'c, 'm> {
+    /// A command and the remaining arguments
+    Command(&'static Command, &'m str),
+    /// A group without command of its own that's missing a valid subcommand
+    Group(&'c Entry),
+}
 
 #[distributed_slice]
 static COMMANDS_SLICE: [Command] = [..];
 
+#[distributed_slice]
+static LISTENERS_SLICE: [Listener] = [..];
+
+#[distributed_slice]
+static TASKS_SLICE: [Task] = [..];
+
 static COMMANDS: OnceLock<Commands> = OnceLock::new();
 
 impl Commands {
+    /// Get all registered commands.
+    ///
+    /// Panics if names collide and [`init_commands`] hasn't caught that at startup.
     pub fn get() -> &'static Self {
-        COMMANDS.get_or_init(|| {
-            let mut cmds = HashMap::new();
+        COMMANDS.get_or_init(|| match Self::build(&COMMANDS_SLICE) {
+            Ok(cmds) => cmds,
+            Err(conflicts) => panic!("{}", format_conflicts(&conflicts)),
+        })
+    }
 
-            for cmd in COMMANDS_SLICE {
-                let names = iter::once(cmd.name).chain(cmd.aliases.iter().copied());
+    /// Index the commands by name and alias, collecting every name that's
+    /// claimed more than once, ignoring case.
+    fn build(cmds: &'static [Command]) -> Result<Self, Vec<Conflict>> {
+        let mut entries: Vec<Entry> = Vec::new();
+        let mut names: HashMap<String, usize> = HashMap::new();
+        let mut conflicts = Vec::new();
 
-                for name in names {
-                    if cmds.insert(name, cmd.run).is_some() {
-                        panic!("command `{name}` has been defined multiple times");
-                    }
+        // top-level commands first so that groups find the command of their name
+        for cmd in cmds.iter().filter(|cmd| cmd.group.is_none()) {
+            for name in cmd.names() {
+                if let Some(&idx) = names.get(&name) {
+                    let first = entries[idx].any_command();
+                    conflicts.push(Conflict::new(name, first, cmd));
+                } else {
+                    names.insert(name, entries.len());
                 }
             }
 
-            Self(cmds)
-        })
+            entries.push(Entry::new(cmd.name, Some(cmd)));
+        }
+
+        for cmd in cmds {
+            let Some(group) = cmd.group else { continue };
+            let key = group.to_lowercase();
+
+            let idx = match names.get(&key) {
+                Some(&idx) if entries[idx].name.eq_ignore_ascii_case(group) => idx,
+                // the group's name is an alias of another command
+                Some(&idx) => {
+                    let first = entries[idx].any_command();
+                    conflicts.push(Conflict::new(key, first, cmd));
+
+                    continue;
+                }
+                None => {
+                    names.insert(key.clone(), entries.len());
+                    entries.push(Entry::new(group, None));
+
+                    entries.len() - 1
+                }
+            };
+
+            let entry = &mut entries[idx];
+
+            for name in cmd.names() {
+                if let Some(&sub_idx) = entry.subcommand_names.get(&name) {
+                    let first = entry.subcommands[sub_idx];
+                    conflicts.push(Conflict::new(format!("{key} {name}"), first, cmd));
+                } else {
+                    entry.subcommand_names.insert(name, entry.subcommands.len());
+                }
+            }
+
+            entry.subcommands.push(cmd);
+        }
+
+        if conflicts.is_empty() {
+            Ok(Self { entries, names })
+        } else {
+            Err(conflicts)
+        }
+    }
+
+    /// Get the top-level command or group by name or alias.
+    fn entry(&self, name: &str) -> Option<&Entry> {
+        let idx = *self.names.get(&name.to_lowercase())?;
+
+        Some(&self.entries[idx])
+    }
+
+    /// Closest name or alias to the unknown lowercase name among the commands
+    /// that are enabled in the channel.
+    fn suggest(&self, name: &str, settings: &ChannelSettings) -> Option<&str> {
+        let candidates = self
+            .names
+            .iter()
+            .filter(|(_, &idx)| {
+                let entry_name = self.entries[idx].name;
+
+                settings.is_enabled(entry_name) || entry_name == "config"
+            })
+            .map(|(name, _)| name.as_str());
+
+        did_you_mean(name, candidates)
+    }
+
+    /// Find the command for the first word of a message, taking the next
+    /// word as subcommand if the first one names a group.
+    fn resolve<'m>(&self, name: &str, rest: &'m str) -> Option<Resolved<'_, 'm>> {
+        let entry = self.entry(name)?;
+        let (sub_name, sub_rest) = split_word(rest);
+
+        if let Some(subcommand) = entry.subcommand(sub_name) {
+            return Some(Resolved::Command(subcommand, sub_rest));
+        }
+
+        match entry.command {
+            Some(cmd) => Some(Resolved::Command(cmd, rest)),
+            None => Some(Resolved::Group(entry)),
+        }
+    }
+}
+
+/// A name or alias that's used by two commands.
+struct Conflict {
+    name: String,
+    first: &'static Command,
+    second: &'static Command,
+}
+
+impl Conflict {
+    fn new(name: String, first: &'static Command, second: &'static Command) -> Self {
+        Self {
+            name,
+            first,
+            second,
+        }
     }
+}
 
-    pub fn command(&self, name: &str) -> Option<CommandFn> {
-        self.0.get(name).copied()
+impl Display for Conflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "`{}` is used by both `{}` ({}) and `{}` ({})",
+            self.name,
+            self.first.qualified_name,
+            self.first.location,
+            self.second.qualified_name,
+            self.second.location,
+        )
     }
 }
 
+fn format_conflicts(conflicts: &[Conflict]) -> String {
+    conflicts.iter().fold(
+        "conflicting command names:".to_owned(),
+        |content, conflict| format!("{content}\n  {conflict}"),
+    )
+}
+
+/// Index the registered commands, failing if any of their names collide.
+pub fn init_commands() -> Result<()> {
+    let cmds = Commands::build(&COMMANDS_SLICE)
+        .map_err(|conflicts| eyre!(format_conflicts(&conflicts)))?;
+    let _ = COMMANDS.set(cmds);
+
+    Ok(())
+}
+
+/// Used if neither the channel nor the platform have a prefix configured
+pub const DEFAULT_PREFIX: &str = "!";
+
 pub async fn handle_command(ctx: Arc<Context>, origin: CommandOrigin<'_>, msg: &str) -> Result<()> {
-    let Some(stripped_prefix) = msg.strip_prefix('!') else {
+    dispatch_command(ctx, origin, msg).await.map(|_| ())
+}
+
+/// Handle the message as command, returning whether it was one.
+///
+/// Messages that are ignored because of the channel settings or ratelimits
+/// count as commands.
+async fn dispatch_command(ctx: Arc<Context>, origin: CommandOrigin<'_>, msg: &str) -> Result<bool> {
+    let settings = ctx.db.channel_settings(&origin.channel_key()).await;
+    let prefix = ctx.prefix(&origin, &settings).await;
+
+    let Some(stripped_prefix) =
+        strip_prefix(msg, &prefix).or_else(|| strip_mention(origin.bot_name(), msg))
+    else {
         // missing prefix
-        return Ok(());
+        return Ok(false);
     };
 
-    let (mut next_word, rest) = stripped_prefix
-        .split_once(' ')
-        .unwrap_or((stripped_prefix, ""));
+    let (next_word, num, rest) = split_command(stripped_prefix);
 
-    let mut num = None;
+    let Some(resolved) = Commands::get().resolve(next_word, rest) else {
+        return suggest_command(&ctx, &origin, &settings, &prefix, next_word).await;
+    };
+
+    let (cmd, top_level_name) = match resolved {
+        Resolved::Command(cmd, _) => (Some(cmd), cmd.top_level_name()),
+        Resolved::Group(entry) => (None, entry.name),
+    };
+
+    // `config` can't be disabled so that admins don't lock themselves out
+    if !(settings.is_enabled(top_level_name) || top_level_name == "config") {
+        return Ok(true);
+    }
+
+    match ctx.ratelimiter.check(cmd, &origin) {
+        Ok(()) => {}
+        Err(Ratelimited::Notify(wait)) => {
+            debug!(name = top_level_name, ?wait, "Ratelimited command");
+            let secs = wait.as_secs() + 1;
+
+            origin
+                .send(&format!("slow down! try again in {secs}s"))
+                .await?;
 
-    if let Some(first_digit_idx) = next_word
-        .bytes()
-        .rev()
-        .position(|byte| byte.is_ascii_alphabetic())
-        .filter(|&idx| idx > 0)
-    {
-        let (front, back) = next_word.split_at(next_word.len() - first_digit_idx);
-        next_word = front;
-        num = back.parse::<u32>().ok();
+            return Ok(true);
+        }
+        Err(Ratelimited::Silent) => return Ok(true),
     }
 
-    let Some(cmd_fn) = Commands::get().command(next_word) else {
-        // unknown command name
-        return Ok(());
+    let (cmd, rest) = match resolved {
+        Resolved::Command(cmd, rest) => (cmd, rest),
+        Resolved::Group(entry) => {
+            origin.send(&entry.usage(&prefix)).await?;
+
+            return Ok(true);
+        }
     };
 
-    info!(name = next_word, num, rest, "Processing command");
+    if !cmd.permission.is_granted(&ctx, &origin).await? {
+        origin
+            .send("you don't have permission to use this command")
+            .await?;
+
+        return Ok(true);
+    }
+
+    info!(name = cmd.qualified_name, num, rest, "Processing command");
+    ctx.stats.record(cmd.qualified_name);
 
     let args = Args { msg: rest, num };
 
-    (cmd_fn)(ctx, origin, args).await
+    let reply = match (cmd.run)(ctx, origin, args).await {
+        Ok(reply) => reply,
+        Err(err) => CommandError::from(err).into_reply(cmd.qualified_name),
+    };
+
+    origin.reply(reply).await?;
+
+    Ok(true)
+}
+
+/// Reply with the closest command name if the unknown name looks like a typo,
+/// returning whether a suggestion was sent.
+async fn suggest_command(
+    ctx: &Context,
+    origin: &CommandOrigin<'_>,
+    settings: &ChannelSettings,
+    prefix: &str,
+    name: &str,
+) -> Result<bool> {
+    if settings.no_suggestions || !name.chars().all(char::is_alphanumeric) {
+        return Ok(false);
+    }
+
+    let name = name.to_lowercase();
+
+    let Some(suggestion) = Commands::get().suggest(&name, settings) else {
+        return Ok(false);
+    };
+
+    // chatter that happens to start with the prefix shouldn't be answered every time
+    if !ctx.ratelimiter.check_suggestion(origin) {
+        return Ok(false);
+    }
+
+    debug!(name, suggestion, "Suggesting command");
+
+    origin
+        .send(&format!("did you mean `{prefix}{suggestion}`?"))
+        .await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockOrigin, TestContext};
+
+    #[test]
+    fn command_names_are_unique() {
+        if let Err(conflicts) = Commands::build(&COMMANDS_SLICE) {
+            panic!("{}", format_conflicts(&conflicts));
+        }
+    }
+
+    fn noop(
+        _: Arc<Context>,
+        _: CommandOrigin<'_>,
+        _: Args<'_>,
+    ) -> BoxFuture<'static, Result<Reply>> {
+        Box::pin(async { Ok(Reply::None) })
+    }
+
+    const fn command(name: &'static str, aliases: &'static [&'static str]) -> Command {
+        subcommand(None, name, aliases)
+    }
+
+    const fn subcommand(
+        group: Option<&'static str>,
+        name: &'static str,
+        aliases: &'static [&'static str],
+    ) -> Command {
+        Command {
+            name,
+            group,
+            // only used in messages, good enough for the tests' commands
+            qualified_name: name,
+            description: "",
+            aliases,
+            cooldown: None,
+            permission: Permission::Everyone,
+            location: "src/commands/test.rs:1",
+            run: noop,
+        }
+    }
+
+    #[test]
+    fn conflicting_names_are_reported() {
+        static CMDS: [Command; 3] = [
+            command("recent", &["rs"]),
+            command("recentset", &["RS", "recentscore"]),
+            command("Recent", &[]),
+        ];
+
+        let Err(conflicts) = Commands::build(&CMDS) else {
+            panic!("expected conflicts");
+        };
+
+        let conflicts: Vec<_> = conflicts.iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            conflicts,
+            [
+                "`rs` is used by both `recent` (src/commands/test.rs:1) and `recentset` (src/commands/test.rs:1)",
+                "`recent` is used by both `recent` (src/commands/test.rs:1) and `Recent` (src/commands/test.rs:1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn conflicting_groups_are_reported() {
+        static CMDS: [Command; 4] = [
+            command("track", &["t"]),
+            subcommand(Some("track"), "add", &["a"]),
+            subcommand(Some("track"), "remove", &["A"]),
+            subcommand(Some("t"), "list", &[]),
+        ];
+
+        let Err(conflicts) = Commands::build(&CMDS) else {
+            panic!("expected conflicts");
+        };
+
+        let names: Vec<_> = conflicts
+            .iter()
+            .map(|conflict| conflict.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["track a", "t"]);
+    }
+
+    #[test]
+    fn resolves_subcommands() {
+        static CMDS: [Command; 3] = [
+            command("config", &[]),
+            subcommand(Some("config"), "prefix", &["p"]),
+            subcommand(Some("track"), "add", &[]),
+        ];
+
+        let commands = Commands::build(&CMDS).unwrap_or_else(|_| panic!("unexpected conflicts"));
+
+        let resolve = |name, rest| match commands.resolve(name, rest) {
+            Some(Resolved::Command(cmd, rest)) => Some((cmd.name, rest)),
+            Some(Resolved::Group(entry)) => Some((entry.name, "<group>")),
+            None => None,
+        };
+
+        assert_eq!(resolve("config", "prefix ?"), Some(("prefix", "?")));
+        assert_eq!(resolve("CONFIG", "P ?"), Some(("prefix", "?")));
+        assert_eq!(resolve("config", ""), Some(("config", "")));
+        assert_eq!(resolve("config", "other"), Some(("config", "other")));
+        assert_eq!(resolve("track", "add peppy"), Some(("add", "peppy")));
+        assert_eq!(resolve("track", "remove"), Some(("track", "<group>")));
+        assert_eq!(resolve("add", ""), None);
+    }
+
+    #[tokio::test]
+    async fn command_names_are_case_insensitive() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!PING").await.unwrap();
+        assert_eq!(origin.take_single(), "pong!");
+    }
+
+    #[tokio::test]
+    async fn dispatches_with_prefix() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!ping").await.unwrap();
+        assert_eq!(origin.take_single(), "pong!");
+
+        test.run(&origin, "!p").await.unwrap();
+        assert_eq!(origin.take_single(), "pong!");
+    }
+
+    #[tokio::test]
+    async fn ignores_unprefixed_and_unknown() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "ping").await.unwrap();
+        test.run(&origin, "!doesnotexist").await.unwrap();
+        test.run(&origin, "?ping").await.unwrap();
+
+        assert!(origin.take_sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn suggests_mistyped_commands() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "!rcent peppy").await.unwrap();
+        assert_eq!(origin.take_single(), "did you mean `!recent`?");
+
+        test.run(&origin, "!OSY").await.unwrap();
+        assert_eq!(origin.take_single(), "did you mean `!osu`?");
+
+        // the channel ran out of suggestions
+        test.run(&origin, "!recnt").await.unwrap();
+        assert!(origin.take_sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn suggestions_can_be_turned_off() {
+        let test = TestContext::new().await;
+        let owner = MockOrigin::new("owner");
+
+        test.run(&owner, "!config suggestions off").await.unwrap();
+        assert_eq!(owner.take_single(), "turned suggestions off");
+
+        test.run(&owner, "!rcent").await.unwrap();
+        assert!(owner.take_sent().is_empty());
+
+        // disabled commands aren't suggested either
+        test.run(&owner, "!config suggestions on").await.unwrap();
+        test.run(&owner, "!config disable recent").await.unwrap();
+        owner.take_sent();
+
+        test.run(&owner, "!rcent").await.unwrap();
+        assert!(owner.take_sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dispatches_with_mention() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.run(&origin, "soban: ping").await.unwrap();
+        assert_eq!(origin.take_single(), "pong!");
+    }
+
+    #[tokio::test]
+    async fn channel_prefix_replaces_default() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.ctx
+            .db
+            .update_channel_settings("irc:#test", |settings| {
+                settings.prefix = Some("?".to_owned())
+            })
+            .await
+            .unwrap();
+
+        test.run(&origin, "!ping").await.unwrap();
+        assert!(origin.take_sent().is_empty());
+
+        test.run(&origin, "?ping").await.unwrap();
+        assert_eq!(origin.take_single(), "pong!");
+    }
+
+    #[tokio::test]
+    async fn denied_commands_are_ignored() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        test.ctx
+            .db
+            .update_channel_settings("irc:#test", |settings| {
+                settings.denied.insert("ping".to_owned())
+            })
+            .await
+            .unwrap();
+
+        test.run(&origin, "!p").await.unwrap();
+        assert!(origin.take_sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn checks_permissions() {
+        let test = TestContext::new().await;
+
+        let user = MockOrigin::new("user");
+        test.run(&user, "!status").await.unwrap();
+        assert_eq!(
+            user.take_single(),
+            "you don't have permission to use this command"
+        );
+
+        let owner = MockOrigin::new("owner");
+        test.run(&owner, "!status").await.unwrap();
+        assert!(owner.take_single().starts_with("uptime"));
+    }
+
+    #[tokio::test]
+    async fn ratelimits_spam() {
+        let test = TestContext::new().await;
+        let origin = MockOrigin::new("user");
+
+        for _ in 0..5 {
+            test.run(&origin, "!ping").await.unwrap();
+        }
+
+        let sent = origin.take_sent();
+
+        // the user bucket allows a burst of 3, then a single notice follows
+        assert_eq!(sent[..3], ["pong!", "pong!", "pong!"]);
+        assert!(sent[3].starts_with("slow down!"));
+        assert_eq!(sent.len(), 4);
+    }
 }
@@ -0,0 +1,140 @@
+use std::sync::{Arc, LazyLock};
+
+use eyre::Result;
+use futures::future::BoxFuture;
+use regex::{Captures, Regex};
+
+use crate::{dispatch_command, CommandOrigin, Context, Platform, LISTENERS_SLICE};
+
+/// Kinds of events that listeners can subscribe to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// A message that wasn't handled as command
+    Message,
+    /// An action like `/me` or `/np`
+    Action,
+    /// The user joined the channel
+    Join,
+    /// The user reacted to a message
+    Reaction,
+}
+
+/// Something that happened in a channel, passed to listeners.
+pub struct Event<'a> {
+    pub kind: EventKind,
+    /// Content of a message or action, the key of a reaction, or empty for joins
+    pub text: &'a str,
+    /// Captures of the listener's regex if it has one
+    pub captures: Option<Captures<'a>>,
+}
+
+pub(crate) type ListenerFn =
+    for<'a> fn(Arc<Context>, CommandOrigin<'a>, Event<'a>) -> BoxFuture<'a, Result<()>>;
+
+/// Handler for events other than commands, registered through `#[listener]`.
+///
+/// Fields are visible to the crate so that the macro can construct it anywhere.
+pub struct Listener {
+    pub(crate) name: &'static str,
+    /// Events the listener handles, all of them if empty
+    pub(crate) events: &'static [EventKind],
+    /// Platform the listener is restricted to
+    pub(crate) platform: Option<Platform>,
+    /// Pattern that the event's text must match
+    pub(crate) regex: Option<&'static LazyLock<Regex>>,
+    /// `file:line` of the listener's definition
+    pub(crate) location: &'static str,
+    pub(crate) run: ListenerFn,
+}
+
+impl Listener {
+    /// Check the filters against the event, returning the regex's captures
+    /// if the listener has one.
+    fn filter<'t>(
+        &self,
+        origin: &CommandOrigin<'_>,
+        kind: EventKind,
+        text: &'t str,
+    ) -> Option<Option<Captures<'t>>> {
+        if !(self.events.is_empty() || self.events.contains(&kind)) {
+            return None;
+        }
+
+        if self
+            .platform
+            .is_some_and(|platform| platform != origin.platform())
+        {
+            return None;
+        }
+
+        match self.regex {
+            Some(regex) => regex.captures(text).map(Some),
+            None => Some(None),
+        }
+    }
+}
+
+/// Handle anything that happened in a channel.
+///
+/// Messages are first handled as commands, only if they aren't one are they
+/// passed on to the listeners.
+pub async fn handle_event(
+    ctx: Arc<Context>,
+    origin: CommandOrigin<'_>,
+    kind: EventKind,
+    text: &str,
+) -> Result<()> {
+    // the bot's own messages could otherwise trigger listeners in a loop
+    if origin.is_from_bot() {
+        return Ok(());
+    }
+
+    if kind == EventKind::Message && dispatch_command(Arc::clone(&ctx), origin, text).await? {
+        return Ok(());
+    }
+
+    let settings = ctx.db.channel_settings(&origin.channel_key()).await;
+
+    for listener in LISTENERS_SLICE {
+        if !settings.is_enabled(listener.name) {
+            continue;
+        }
+
+        let Some(captures) = listener.filter(&origin, kind, text) else {
+            continue;
+        };
+
+        // listeners aren't invoked on purpose so there's no need to notify
+        if !ctx.ratelimiter.check_listener(&origin) {
+            debug!(name = listener.name, "Ratelimited listener");
+
+            return Ok(());
+        }
+
+        debug!(name = listener.name, ?kind, "Running listener");
+
+        let event = Event {
+            kind,
+            text,
+            captures,
+        };
+
+        if let Err(err) = (listener.run)(Arc::clone(&ctx), origin, event).await {
+            error!(
+                name = listener.name,
+                location = listener.location,
+                ?err,
+                "Failed to run listener"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Get a registered listener by name.
+pub fn listener(name: &str) -> Option<&'static Listener> {
+    LISTENERS_SLICE
+        .iter()
+        .find(|listener| listener.name.eq_ignore_ascii_case(name))
+}
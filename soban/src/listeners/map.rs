@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use rosu_v2::prelude::{Beatmap, OsuError};
+use soban_macros::listener;
+
+use crate::{
+    utils::beatmap::{map_name, MapAttributes, MAP_LINK_REGEX},
+    CommandOrigin, Context, Event,
+};
+
+/// Show details of maps that are linked in messages or `/np` actions.
+#[listener(events("message", "action"), regex = MAP_LINK_REGEX)]
+async fn map_link(ctx: Arc<Context>, origin: CommandOrigin<'_>, event: Event<'_>) -> Result<()> {
+    let Some(map_id) = event
+        .captures
+        .as_ref()
+        .and_then(|captures| captures.get(1))
+        .and_then(|id| id.as_str().parse::<u32>().ok())
+    else {
+        return Ok(());
+    };
+
+    let map = match ctx.cache.beatmap(&*ctx.osu, map_id, false).await {
+        Ok(map) => map,
+        // nobody asked for it so there's no need to complain
+        Err(OsuError::NotFound) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+
+    origin.send(&format_map(&map)).await
+}
+
+fn format_map(map: &Beatmap) -> String {
+    let name = map_name(map);
+    let attrs = MapAttributes::from_info(map);
+
+    format!(
+        "{name} | ★{stars:.2} | {length} | {bpm:.0} BPM | {attrs} | {status:?}",
+        stars = map.stars,
+        length = attrs.length(),
+        bpm = attrs.bpm,
+        status = map.status,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{MockOrigin, TestContext},
+        EventKind,
+    };
+
+    const LINK: &str = "check out https://osu.ppy.sh/b/1 :)";
+    const DETAILS: &str = "[Test] | ★4.50 | 0:10 | 200 BPM | CS4 AR9 OD8 HP5 | Ranked";
+
+    #[tokio::test]
+    async fn links_show_map_details() {
+        let test = TestContext::new().await;
+        let user = MockOrigin::new("user");
+
+        test.event(&user, EventKind::Message, LINK).await.unwrap();
+        assert_eq!(user.take_single(), DETAILS);
+
+        test.event(
+            &user,
+            EventKind::Action,
+            "is listening to [https://osu.ppy.sh/beatmapsets/1#osu/1 Test]",
+        )
+        .await
+        .unwrap();
+        assert_eq!(user.take_single(), DETAILS);
+
+        // the api info is enough without mods
+        let requests = test.stand_in.requests();
+        assert!(!requests.iter().any(|path| path.starts_with("/osu/")));
+    }
+
+    #[tokio::test]
+    async fn other_events_are_ignored() {
+        let test = TestContext::new().await;
+        let user = MockOrigin::new("user");
+        let bot = MockOrigin::new(MockOrigin::BOT_NAME);
+
+        test.event(&user, EventKind::Message, "no links here")
+            .await
+            .unwrap();
+        test.event(&user, EventKind::Reaction, LINK).await.unwrap();
+        test.event(&bot, EventKind::Message, LINK).await.unwrap();
+        assert!(user.take_sent().is_empty());
+        assert!(bot.take_sent().is_empty());
+
+        // commands are never passed on to listeners
+        test.event(&user, EventKind::Message, "!ping https://osu.ppy.sh/b/1")
+            .await
+            .unwrap();
+        assert_eq!(user.take_single(), "pong!");
+    }
+
+    #[tokio::test]
+    async fn links_dont_ratelimit_commands() {
+        let test = TestContext::new().await;
+        let user = MockOrigin::new("user");
+
+        for _ in 0..4 {
+            test.event(&user, EventKind::Message, LINK).await.unwrap();
+        }
+
+        // the listener's own bucket allows a burst of 3
+        assert_eq!(user.take_sent(), [DETAILS; 3]);
+
+        for _ in 0..3 {
+            test.run(&user, "!ping").await.unwrap();
+        }
+
+        assert_eq!(user.take_sent(), ["pong!"; 3]);
+    }
+
+    #[tokio::test]
+    async fn listeners_can_be_disabled() {
+        let test = TestContext::new().await;
+        let owner = MockOrigin::new("owner");
+
+        test.run(&owner, "!config disable map_link").await.unwrap();
+        assert_eq!(owner.take_single(), "disabled `map_link`");
+
+        test.event(&owner, EventKind::Message, LINK).await.unwrap();
+        assert!(owner.take_sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unknown_maps_are_ignored() {
+        let test = TestContext::new().await;
+        let user = MockOrigin::new("user");
+
+        test.event(&user, EventKind::Message, "https://osu.ppy.sh/b/404")
+            .await
+            .unwrap();
+        assert!(user.take_sent().is_empty());
+    }
+}
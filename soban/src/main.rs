@@ -7,18 +7,30 @@ extern crate tracing;
 use eyre::Result;
 use futures::StreamExt;
 use irc::{
-    client::{prelude::Config, Client, Sender},
-    proto::{Command, Message},
+    client::{prelude::Config, Client},
+    proto::{Capability, Command, Message},
 };
 use matrix_sdk::{
     config::SyncSettings,
     room::Room,
-    ruma::events::room::message::{MessageType, OriginalSyncRoomMessageEvent},
+    ruma::{
+        events::{
+            reaction::OriginalSyncReactionEvent,
+            room::{
+                member::{MembershipChange, OriginalSyncRoomMemberEvent},
+                message::{MessageType, OriginalSyncRoomMessageEvent},
+            },
+        },
+        UserId,
+    },
     Client as MatrixClient,
 };
 use rosu_v2::Osu;
-use soban::{handle_command, CommandOrigin, Context};
-use std::{env, sync::Arc};
+use soban::{
+    handle_event, init_commands, owners_from_env, spawn_tasks, CommandOrigin, Context, Database,
+    EventKind, IrcOrigin, MatrixOrigin,
+};
+use std::{collections::HashSet, env, path::PathBuf, sync::Arc};
 
 struct MatrixConfig {
     homeserver: String,
@@ -27,6 +39,9 @@ struct MatrixConfig {
 }
 
 struct BotConfig {
+    database_path: String,
+    owners: HashSet<String>,
+    map_path: PathBuf,
     osu_client_id: u64,
     osu_client_secret: String,
     irc_config: Config,
@@ -35,6 +50,18 @@ struct BotConfig {
 
 impl BotConfig {
     pub fn init() -> Result<Self> {
+        let database_path = match env::var("DATABASE_PATH") {
+            Ok(val) => val,
+            Err(_) => return Err(eyre!("Missing DATABASE_PATH environment variable")),
+        };
+
+        let owners = owners_from_env();
+
+        let map_path = match env::var("OSU_MAP_PATH") {
+            Ok(val) => PathBuf::from(val),
+            Err(_) => return Err(eyre!("Missing OSU_MAP_PATH environment variable")),
+        };
+
         let osu_client_id = match env::var("OSU_CLIENT_ID") {
             Ok(val) => val.parse()?,
             Err(_) => return Err(eyre!("Missing OSU_CLIENT_ID environment variable")),
@@ -74,6 +101,9 @@ impl BotConfig {
         };
 
         Ok(BotConfig {
+            database_path,
+            owners,
+            map_path,
             osu_client_id,
             osu_client_secret,
             irc_config,
@@ -87,10 +117,23 @@ async fn main() -> Result<()> {
     dotenvy::dotenv()?;
 
     tracing_subscriber::fmt::init();
-    let config = BotConfig::init()?;
+    init_commands()?;
+    let mut config = BotConfig::init()?;
 
     let osu = Osu::new(config.osu_client_id, config.osu_client_secret).await?;
-    let context = Arc::new(Context { osu });
+    let db = Database::open(&config.database_path).await?;
+
+    // channels are managed through `!join` and `!part` after the first start
+    config.irc_config.channels = db.irc_channels_or_init(&config.irc_config.channels).await?;
+
+    let context = Arc::new(Context::new(
+        Box::new(osu),
+        db,
+        config.owners,
+        config.map_path,
+    ));
+
+    spawn_tasks(Arc::clone(&context));
 
     let irc_client = Client::from_config(config.irc_config).await?;
     let matrix_client = MatrixClient::builder()
@@ -128,16 +171,40 @@ async fn run_matrix_client(
         .login_username(username, password)
         .send()
         .await?;
+    let _ = context.platforms.matrix.set(matrix_client.clone());
+
     let response = matrix_client
         .sync_once(SyncSettings::default())
         .await
         .unwrap();
 
+    let ctx = Arc::clone(&context);
+
     matrix_client.add_event_handler(move |ev: OriginalSyncRoomMessageEvent, room: Room| {
-        let ctx = Arc::clone(&context);
+        let ctx = Arc::clone(&ctx);
 
         async move { process_matrix_message(ctx, ev, room).await }
     });
+
+    let ctx = Arc::clone(&context);
+
+    matrix_client.add_event_handler(move |ev: OriginalSyncReactionEvent, room: Room| {
+        let ctx = Arc::clone(&ctx);
+        let key = ev.content.relates_to.key;
+
+        async move { process_matrix_event(ctx, room, &ev.sender, EventKind::Reaction, &key).await }
+    });
+
+    matrix_client.add_event_handler(move |ev: OriginalSyncRoomMemberEvent, room: Room| {
+        let ctx = Arc::clone(&context);
+
+        async move {
+            if matches!(ev.membership_change(), MembershipChange::Joined) {
+                process_matrix_event(ctx, room, &ev.sender, EventKind::Join, "").await;
+            }
+        }
+    });
+
     let settings = SyncSettings::default().token(response.next_batch);
     matrix_client.sync(settings).await?;
 
@@ -149,39 +216,76 @@ async fn process_matrix_message(
     event: OriginalSyncRoomMessageEvent,
     room: Room,
 ) {
-    let Room::Joined(ref room) = room else {
-        return;
+    let (kind, body) = match event.content.msgtype {
+        MessageType::Text(content) => (EventKind::Message, content.body),
+        MessageType::Emote(content) => (EventKind::Action, content.body),
+        _ => return,
     };
-    let MessageType::Text(text_content) = event.content.msgtype else {
+
+    process_matrix_event(context, room, &event.sender, kind, &body).await;
+}
+
+async fn process_matrix_event(
+    context: Arc<Context>,
+    room: Room,
+    user: &UserId,
+    kind: EventKind,
+    text: &str,
+) {
+    let Room::Joined(ref room) = room else {
         return;
     };
 
-    let origin = CommandOrigin::Matrix { room };
+    let origin = MatrixOrigin { room, user };
 
-    if let Err(err) = handle_command(context, origin, &text_content.body).await {
-        error!(?err, "Failed to handle matrix cmd");
+    if let Err(err) = handle_event(context, CommandOrigin::new(&origin), kind, text).await {
+        error!(?err, ?kind, "Failed to handle matrix event");
     }
 }
 
 async fn run_irc_client(context: Arc<Context>, mut irc_client: Client) -> Result<()> {
+    // tags messages with the author's services account, used for permissions
+    irc_client.send_cap_req(&[Capability::AccountTag])?;
     irc_client.identify()?;
+    let _ = context.platforms.irc.set(irc_client.sender());
 
     let mut stream = irc_client.stream()?;
-    let sender = irc_client.sender();
 
     while let Some(message) = stream.next().await.transpose()? {
-        process_irc_message(context.clone(), &sender, message).await;
+        process_irc_message(context.clone(), &irc_client, message).await;
     }
 
     Ok(())
 }
 
-async fn process_irc_message(context: Arc<Context>, sender: &Sender, message: Message) {
-    if let Command::PRIVMSG(ref target, ref msg) = message.command {
-        let origin = CommandOrigin::Irc { sender, target };
-
-        if let Err(err) = handle_command(context, origin, msg).await {
-            error!(?err, "Failed to handle irc cmd");
+async fn process_irc_message(context: Arc<Context>, client: &Client, message: Message) {
+    let (target, kind, text) = match message.command {
+        Command::PRIVMSG(ref target, ref msg) => {
+            // CTCP actions like `/me` are wrapped in `\x01ACTION ...\x01`
+            match msg
+                .strip_prefix("\x01ACTION ")
+                .map(|action| action.trim_end_matches('\x01'))
+            {
+                Some(action) => (target, EventKind::Action, action),
+                None => (target, EventKind::Message, msg.as_str()),
+            }
         }
+        Command::JOIN(ref channel, ..) => (channel, EventKind::Join, ""),
+        _ => return,
+    };
+
+    let Some(nick) = message.source_nickname() else {
+        return;
+    };
+
+    let origin = IrcOrigin {
+        client,
+        target,
+        nick,
+        tags: message.tags.as_deref().unwrap_or_default(),
+    };
+
+    if let Err(err) = handle_event(context, CommandOrigin::new(&origin), kind, text).await {
+        error!(?err, ?kind, "Failed to handle irc event");
     }
 }
@@ -0,0 +1,457 @@
+use std::ops::Deref;
+
+use eyre::Result;
+use futures::{future::BoxFuture, FutureExt};
+use irc::{
+    client::{data::AccessLevel, Client, Sender},
+    proto::message::Tag,
+};
+use matrix_sdk::{
+    attachment::AttachmentConfig,
+    room::Joined,
+    ruma::{events::room::message::RoomMessageEventContent, UserId},
+};
+
+use crate::{Author, Embed, Platform, Reply};
+
+/// Matrix power level from which on users count as moderators
+const MODERATOR_POWER_LEVEL: i64 = 50;
+
+/// Bytes of text per irc message, which leaves room for the prefix that the
+/// server adds within its 512 byte line limit
+const IRC_MESSAGE_LENGTH: usize = 400;
+
+/// Where a command or event came from and how to answer it, implemented once
+/// per platform.
+pub trait Origin: Send + Sync {
+    fn send<'a>(&'a self, msg: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Send a single message of a reply, formatted for the platform.
+    fn send_reply(&self, reply: Reply) -> BoxFuture<'_, Result<()>>;
+
+    /// Name of the bot on the platform, used to recognize mentions.
+    fn bot_name(&self) -> &str;
+
+    /// Whether the bot itself is the author.
+    fn is_from_bot(&self) -> bool;
+
+    fn platform(&self) -> Platform;
+
+    /// The irc channel or matrix room id the command was sent in.
+    fn channel(&self) -> &str;
+
+    /// The user that sent the command.
+    fn author(&self) -> Author<'_>;
+
+    /// Whether the author moderates the channel or room the command was sent in.
+    fn is_channel_moderator(&self) -> BoxFuture<'_, Result<bool>>;
+}
+
+/// Handle to the [`Origin`] of a command, passed to commands and listeners.
+#[derive(Clone, Copy)]
+pub struct CommandOrigin<'a>(&'a dyn Origin);
+
+impl<'a> CommandOrigin<'a> {
+    pub fn new(origin: &'a dyn Origin) -> Self {
+        Self(origin)
+    }
+}
+
+impl<'a> Deref for CommandOrigin<'a> {
+    type Target = dyn Origin + 'a;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl CommandOrigin<'_> {
+    /// Send every message of the reply, formatted for the platform.
+    pub async fn reply(&self, reply: Reply) -> Result<()> {
+        for message in reply.into_messages() {
+            self.0.send_reply(message).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Identifies the channel or room the command was sent in.
+    pub(crate) fn channel_key(&self) -> String {
+        format!("{}:{}", self.platform(), self.channel())
+    }
+
+    /// Identifies the user that sent the command across platforms.
+    pub fn user_key(&self) -> String {
+        self.author().key()
+    }
+
+    /// Identifies the account of the user, see [`Author::account_key`].
+    pub fn account_key(&self) -> Option<String> {
+        self.author().account_key()
+    }
+}
+
+pub struct IrcOrigin<'a> {
+    pub client: &'a Client,
+    pub target: &'a str,
+    pub nick: &'a str,
+    pub tags: &'a [Tag],
+}
+
+impl IrcOrigin<'_> {
+    /// Services account of the author, sent with the IRCv3 `account-tag` capability.
+    fn account(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|tag| tag.0 == "account")
+            .and_then(|tag| tag.1.as_deref())
+    }
+}
+
+impl Origin for IrcOrigin<'_> {
+    fn send<'a>(&'a self, msg: &'a str) -> BoxFuture<'a, Result<()>> {
+        let res = send_irc_lines(&self.client.sender(), self.target, msg);
+
+        async move { res }.boxed()
+    }
+
+    fn send_reply(&self, reply: Reply) -> BoxFuture<'_, Result<()>> {
+        async move {
+            match reply {
+                Reply::Embed(embed) => self.send(&irc_embed_lines(&embed)).await,
+                Reply::File(file) => {
+                    self.send(&format!("`{}` can't be sent on irc", file.name))
+                        .await
+                }
+                reply => match reply.to_plain() {
+                    Some(text) => self.send(&text).await,
+                    None => Ok(()),
+                },
+            }
+        }
+        .boxed()
+    }
+
+    fn bot_name(&self) -> &str {
+        self.client.current_nickname()
+    }
+
+    fn is_from_bot(&self) -> bool {
+        self.nick
+            .eq_ignore_ascii_case(self.client.current_nickname())
+    }
+
+    fn platform(&self) -> Platform {
+        Platform::Irc
+    }
+
+    fn channel(&self) -> &str {
+        self.target
+    }
+
+    fn author(&self) -> Author<'_> {
+        Author {
+            platform: Platform::Irc,
+            id: self.nick,
+            name: self.nick,
+            account: self.account(),
+        }
+    }
+
+    fn is_channel_moderator(&self) -> BoxFuture<'_, Result<bool>> {
+        // twitch sends the author's status as message tags
+        let twitch_mod = self.tags.iter().any(|tag| match (tag.0.as_str(), &tag.1) {
+            ("mod", Some(value)) => value == "1",
+            ("badges", Some(value)) => value
+                .split(',')
+                .any(|badge| badge.starts_with("broadcaster/")),
+            _ => false,
+        });
+
+        let is_operator = || {
+            self.client
+                .list_users(self.target)
+                .unwrap_or_default()
+                .iter()
+                .filter(|user| user.get_nickname() == self.nick)
+                .flat_map(|user| user.access_levels())
+                .any(|level| {
+                    matches!(
+                        level,
+                        AccessLevel::Owner
+                            | AccessLevel::Admin
+                            | AccessLevel::Oper
+                            | AccessLevel::HalfOp
+                    )
+                })
+        };
+
+        let is_moderator = twitch_mod || is_operator();
+
+        async move { Ok(is_moderator) }.boxed()
+    }
+}
+
+/// Send every line of the message as its own `PRIVMSG`, splitting lines that
+/// are too long for a single one.
+///
+/// A line break ends an irc message, so the text after it would otherwise be
+/// read as a raw command by the server.
+pub(crate) fn send_irc_lines(sender: &Sender, target: &str, msg: &str) -> Result<()> {
+    for line in msg.lines().filter(|line| !line.is_empty()) {
+        for part in split_irc_line(line) {
+            sender.send_privmsg(target, part)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Join the parts of the embed with ` | ` into as few lines as fit in irc
+/// messages, since servers kick clients that send many messages at once.
+fn irc_embed_lines(embed: &Embed) -> String {
+    let fields = embed
+        .fields
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"));
+
+    let parts = [embed.title.clone()]
+        .into_iter()
+        .chain(fields)
+        .chain(embed.footer.clone());
+
+    let mut lines = Vec::<String>::new();
+
+    for part in parts {
+        match lines.last_mut() {
+            Some(line) if line.len() + " | ".len() + part.len() <= IRC_MESSAGE_LENGTH => {
+                line.push_str(" | ");
+                line.push_str(&part);
+            }
+            _ => lines.push(part),
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Split the line into parts of at most [`IRC_MESSAGE_LENGTH`] bytes,
+/// preferably at spaces.
+fn split_irc_line(mut line: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+
+    while line.len() > IRC_MESSAGE_LENGTH {
+        let mut end = IRC_MESSAGE_LENGTH;
+
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let end = match line[..end].rfind(' ') {
+            Some(space) if space > 0 => space,
+            _ => end,
+        };
+
+        parts.push(&line[..end]);
+        line = line[end..].trim_start_matches(' ');
+    }
+
+    if !line.is_empty() {
+        parts.push(line);
+    }
+
+    parts
+}
+
+pub struct MatrixOrigin<'a> {
+    pub room: &'a Joined,
+    pub user: &'a UserId,
+}
+
+impl Origin for MatrixOrigin<'_> {
+    fn send<'a>(&'a self, msg: &'a str) -> BoxFuture<'a, Result<()>> {
+        async move {
+            self.room
+                .send(RoomMessageEventContent::text_plain(msg), None)
+                .await?;
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn send_reply(&self, reply: Reply) -> BoxFuture<'_, Result<()>> {
+        async move {
+            match reply {
+                Reply::Embed(embed) => {
+                    let content =
+                        RoomMessageEventContent::text_html(embed.to_plain(), embed.to_html());
+                    self.room.send(content, None).await?;
+                }
+                Reply::File(file) => {
+                    let config = AttachmentConfig::new();
+                    self.room
+                        .send_attachment(&file.name, &file.content_type, &file.data, config)
+                        .await?;
+                }
+                reply => {
+                    if let Some(text) = reply.to_plain() {
+                        self.send(&text).await?;
+                    }
+                }
+            }
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn bot_name(&self) -> &str {
+        self.room.own_user_id().localpart()
+    }
+
+    fn is_from_bot(&self) -> bool {
+        self.user == self.room.own_user_id()
+    }
+
+    fn platform(&self) -> Platform {
+        Platform::Matrix
+    }
+
+    fn channel(&self) -> &str {
+        self.room.room_id().as_str()
+    }
+
+    fn author(&self) -> Author<'_> {
+        Author {
+            platform: Platform::Matrix,
+            id: self.user.as_str(),
+            name: self.user.localpart(),
+            account: Some(self.user.as_str()),
+        }
+    }
+
+    fn is_channel_moderator(&self) -> BoxFuture<'_, Result<bool>> {
+        async move {
+            let power_level = self
+                .room
+                .get_member(self.user)
+                .await?
+                .map_or(0, |member| member.power_level());
+
+            Ok(power_level >= MODERATOR_POWER_LEVEL)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mime::TEXT_PLAIN;
+
+    use crate::{
+        test_support::{IrcServer, TestContext},
+        Attachment, Embed, Permission,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn irc_sends_one_privmsg_per_line() {
+        let mut server = IrcServer::start().await;
+
+        let origin = IrcOrigin {
+            client: &server.client,
+            target: "#osu",
+            nick: "user",
+            tags: &[],
+        };
+
+        let embed = Embed::new("peppy").field("pp", "1234").footer("hi");
+        let file = Attachment {
+            name: "map.osu".to_owned(),
+            content_type: TEXT_PLAIN,
+            data: Vec::new(),
+        };
+
+        let reply = Reply::Multiple(vec![embed.into(), "first\r\n\nsecond".into(), file.into()]);
+
+        CommandOrigin::new(&origin).reply(reply).await.unwrap();
+
+        assert_eq!(
+            server.next_lines(4).await,
+            [
+                "PRIVMSG #osu :peppy | pp: 1234 | hi",
+                "PRIVMSG #osu first",
+                "PRIVMSG #osu second",
+                "PRIVMSG #osu :`map.osu` can't be sent on irc",
+            ]
+        );
+    }
+
+    #[test]
+    fn irc_embeds_fill_few_lines() {
+        let mut embed = Embed::new("recent scores").footer("page 1/1");
+
+        for i in 0..10 {
+            embed = embed.field(format!("#{i}"), "a".repeat(60));
+        }
+
+        let text = irc_embed_lines(&embed);
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("recent scores | #0: aaa"));
+        assert!(lines[1].ends_with(" | page 1/1"));
+        assert!(lines.iter().all(|line| line.len() <= IRC_MESSAGE_LENGTH));
+    }
+
+    #[test]
+    fn long_irc_lines_are_split() {
+        let word = "ö".repeat(30);
+        let line = vec![word.as_str(); 20].join(" ");
+        let parts = split_irc_line(&line);
+
+        assert_eq!(parts.len(), 4);
+        assert!(parts.iter().all(|part| part.len() <= IRC_MESSAGE_LENGTH));
+        assert!(parts
+            .iter()
+            .all(|part| part.starts_with('ö') && part.ends_with('ö')));
+        assert_eq!(parts.join(" "), line);
+
+        let unbroken = "x".repeat(IRC_MESSAGE_LENGTH + 1);
+        assert_eq!(split_irc_line(&unbroken), [&unbroken[..400], "x"]);
+    }
+
+    #[tokio::test]
+    async fn irc_permissions_need_an_account() {
+        let test = TestContext::new().await;
+        let server = IrcServer::start().await;
+
+        let is_owner = |tags: Vec<Tag>| {
+            let ctx = &test.ctx;
+            let client = &server.client;
+
+            async move {
+                let origin = IrcOrigin {
+                    client,
+                    target: "#osu",
+                    nick: "owner",
+                    tags: &tags,
+                };
+
+                Permission::Owner
+                    .is_granted(ctx, &CommandOrigin::new(&origin))
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let account = |name: &str| Tag("account".to_owned(), Some(name.to_owned()));
+
+        // the nick alone can be taken by anyone
+        assert!(!is_owner(Vec::new()).await);
+        assert!(!is_owner(vec![account("someone")]).await);
+        assert!(is_owner(vec![account("OWNER")]).await);
+    }
+}
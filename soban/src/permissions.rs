@@ -0,0 +1,81 @@
+use std::{collections::HashSet, env};
+
+use eyre::Result;
+
+use crate::{CommandOrigin, Context};
+
+/// Role that grants all permissions except for owner-only ones
+const ADMIN_ROLE: &str = "admin";
+
+/// Required permission to use a command, declared via `#[command(permission = "...")]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Permission {
+    Everyone,
+    /// Channel operators, matrix users with sufficient power level, and twitch mods
+    Moderator,
+    /// Users with the `admin` role
+    Admin,
+    /// Bot owners as configured through `BOT_OWNERS`
+    Owner,
+    /// Users with the given custom role
+    Role(&'static str),
+}
+
+impl Permission {
+    /// Check whether the author of the command has this permission.
+    ///
+    /// Owners have every permission and admins have all but [`Permission::Owner`].
+    /// Owners, admins and roles require an account, while moderators are
+    /// vouched for by the channel itself.
+    pub async fn is_granted(self, ctx: &Context, origin: &CommandOrigin<'_>) -> Result<bool> {
+        if self == Self::Everyone {
+            return Ok(true);
+        }
+
+        let Some(user) = origin.account_key() else {
+            return match self {
+                Self::Moderator => origin.is_channel_moderator().await,
+                _ => Ok(false),
+            };
+        };
+
+        if ctx.is_owner(&user) {
+            return Ok(true);
+        }
+
+        if self == Self::Owner {
+            return Ok(false);
+        }
+
+        if ctx.db.has_role(&user, ADMIN_ROLE).await {
+            return Ok(true);
+        }
+
+        match self {
+            Self::Moderator => origin.is_channel_moderator().await,
+            Self::Role(role) => Ok(ctx.db.has_role(&user, role).await),
+            Self::Everyone | Self::Admin | Self::Owner => Ok(false),
+        }
+    }
+}
+
+/// Read the comma-separated account keys of the `BOT_OWNERS` environment variable.
+pub fn owners_from_env() -> HashSet<String> {
+    env::var("BOT_OWNERS")
+        .map(|val| {
+            val.split(',')
+                .map(str::trim)
+                .filter(|owner| !owner.is_empty())
+                .map(normalize_user_key)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Lowercase irc accounts since they're case-insensitive, e.g. `irc:Peppy` to `irc:peppy`.
+pub fn normalize_user_key(key: &str) -> String {
+    match key.strip_prefix("irc:") {
+        Some(account) => format!("irc:{}", account.to_lowercase()),
+        None => key.to_owned(),
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{Command, CommandOrigin};
+
+const GLOBAL: BucketConfig = BucketConfig::new(10, Duration::from_millis(500));
+const PER_CHANNEL: BucketConfig = BucketConfig::new(5, Duration::from_secs(2));
+const PER_USER: BucketConfig = BucketConfig::new(3, Duration::from_secs(5));
+
+/// Limits how often a user is told to slow down
+const NOTICE: BucketConfig = BucketConfig::new(1, Duration::from_secs(30));
+
+/// Limits how often a channel gets suggestions for mistyped commands
+const SUGGESTION: BucketConfig = BucketConfig::new(2, Duration::from_secs(60));
+
+/// Limits how often listeners respond to a user, separately from commands
+const LISTENER: BucketConfig = BucketConfig::new(3, Duration::from_secs(10));
+
+/// Amount of buckets per kind after which full ones are dropped
+const PURGE_THRESHOLD: usize = 512;
+
+/// Token buckets checked before a command is dispatched.
+///
+/// A command only runs if the global bucket, the channel's bucket, the user's
+/// bucket, and, if the command declares a cooldown, the user's bucket for that
+/// command all have a token left. Tokens are only taken if all of them do.
+#[derive(Default)]
+pub struct Ratelimiter {
+    inner: Mutex<Buckets>,
+}
+
+#[derive(Default)]
+struct Buckets {
+    global: Option<Bucket>,
+    channels: HashMap<String, Bucket>,
+    users: HashMap<String, Bucket>,
+    commands: HashMap<(&'static str, String), Bucket>,
+    notices: HashMap<String, Bucket>,
+    suggestions: HashMap<String, Bucket>,
+    listeners: HashMap<String, Bucket>,
+}
+
+pub enum Ratelimited {
+    /// The user should be told how long to wait
+    Notify(Duration),
+    /// The user has been told recently so the command is dropped silently
+    Silent,
+}
+
+impl Ratelimiter {
+    /// Take a token for the command, or for a message that isn't handled by
+    /// a command but still gets a response if `cmd` is `None`.
+    pub fn check(
+        &self,
+        cmd: Option<&Command>,
+        origin: &CommandOrigin<'_>,
+    ) -> Result<(), Ratelimited> {
+        let now = Instant::now();
+        let channel = origin.channel_key();
+        let user = origin.user_key();
+
+        let mut guard = self.inner.lock().unwrap();
+        let buckets = &mut *guard;
+
+        if buckets.users.len() >= PURGE_THRESHOLD {
+            buckets.purge(now);
+        }
+
+        let cooldown = cmd.and_then(|cmd| {
+            let key = (cmd.qualified_name, user.clone());
+
+            cmd.cooldown
+                .map(|cooldown| (BucketConfig::new(1, cooldown), key))
+        });
+
+        let mut wait = [
+            buckets
+                .global
+                .get_or_insert_with(|| GLOBAL.bucket(now))
+                .wait(now),
+            entry(&mut buckets.channels, channel.clone(), &PER_CHANNEL, now),
+            entry(&mut buckets.users, user.clone(), &PER_USER, now),
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+
+        if let Some((ref config, ref key)) = cooldown {
+            wait = wait.max(entry(&mut buckets.commands, key.clone(), config, now));
+        }
+
+        if wait.is_zero() {
+            buckets.global.as_mut().unwrap().take();
+            buckets.channels.get_mut(&channel).unwrap().take();
+            buckets.users.get_mut(&user).unwrap().take();
+
+            if let Some((_, key)) = cooldown {
+                buckets.commands.get_mut(&key).unwrap().take();
+            }
+
+            return Ok(());
+        }
+
+        let notice = buckets
+            .notices
+            .entry(user)
+            .or_insert_with(|| NOTICE.bucket(now));
+
+        if notice.wait(now).is_zero() {
+            notice.take();
+
+            Err(Ratelimited::Notify(wait))
+        } else {
+            Err(Ratelimited::Silent)
+        }
+    }
+
+    /// Take a token for suggesting a command in the channel, returning
+    /// whether there was one left.
+    pub fn check_suggestion(&self, origin: &CommandOrigin<'_>) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.inner.lock().unwrap();
+
+        let bucket = buckets
+            .suggestions
+            .entry(origin.channel_key())
+            .or_insert_with(|| SUGGESTION.bucket(now));
+
+        if bucket.wait(now).is_zero() {
+            bucket.take();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Take a token for a listener responding to the user, returning whether
+    /// there was one left.
+    ///
+    /// Listeners aren't invoked on purpose so they don't take tokens that the
+    /// user's commands need.
+    pub fn check_listener(&self, origin: &CommandOrigin<'_>) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.inner.lock().unwrap();
+
+        let bucket = buckets
+            .listeners
+            .entry(origin.user_key())
+            .or_insert_with(|| LISTENER.bucket(now));
+
+        if bucket.wait(now).is_zero() {
+            bucket.take();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove all buckets that would be full by now anyway.
+    pub fn purge(&self) {
+        self.inner.lock().unwrap().purge(Instant::now());
+    }
+}
+
+fn entry<K: Hash + Eq>(
+    buckets: &mut HashMap<K, Bucket>,
+    key: K,
+    config: &BucketConfig,
+    now: Instant,
+) -> Duration {
+    buckets
+        .entry(key)
+        .or_insert_with(|| config.bucket(now))
+        .wait(now)
+}
+
+impl Buckets {
+    /// Remove all buckets that would be full by now anyway.
+    fn purge(&mut self, now: Instant) {
+        self.channels.retain(|_, bucket| !bucket.is_full(now));
+        self.users.retain(|_, bucket| !bucket.is_full(now));
+        self.commands.retain(|_, bucket| !bucket.is_full(now));
+        self.notices.retain(|_, bucket| !bucket.is_full(now));
+        self.suggestions.retain(|_, bucket| !bucket.is_full(now));
+        self.listeners.retain(|_, bucket| !bucket.is_full(now));
+    }
+}
+
+struct BucketConfig {
+    capacity: u32,
+    /// Time it takes to regain a single token
+    refill: Duration,
+}
+
+impl BucketConfig {
+    const fn new(capacity: u32, refill: Duration) -> Self {
+        Self { capacity, refill }
+    }
+
+    fn bucket(&self, now: Instant) -> Bucket {
+        Bucket {
+            capacity: self.capacity as f64,
+            refill: self.refill,
+            tokens: self.capacity as f64,
+            last_refill: now,
+        }
+    }
+}
+
+struct Bucket {
+    capacity: f64,
+    refill: Duration,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn tokens_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        let gained = elapsed.as_secs_f64() / self.refill.as_secs_f64();
+
+        (self.tokens + gained).min(self.capacity)
+    }
+
+    /// Refill the bucket and return how long it takes until a token is available.
+    fn wait(&mut self, now: Instant) -> Duration {
+        self.tokens = self.tokens_at(now);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            Duration::ZERO
+        } else {
+            self.refill.mul_f64(1.0 - self.tokens)
+        }
+    }
+
+    fn take(&mut self) {
+        self.tokens -= 1.0;
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        self.tokens_at(now) >= self.capacity
+    }
+}
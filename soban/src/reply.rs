@@ -0,0 +1,209 @@
+use mime::Mime;
+
+/// Response of a command, sent to the origin by the dispatcher.
+///
+/// Commands return a reply instead of sending it themselves so that the
+/// output can be formatted per platform and inspected by other callers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Reply {
+    /// Nothing to send, e.g. if the command already responded on its own
+    #[default]
+    None,
+    Text(String),
+    Embed(Embed),
+    /// Several replies that are sent one after another
+    Multiple(Vec<Reply>),
+    File(Attachment),
+}
+
+impl Reply {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Split the reply into the individual messages to send.
+    pub fn into_messages(self) -> Vec<Reply> {
+        match self {
+            Self::None => Vec::new(),
+            Self::Multiple(replies) => replies.into_iter().flat_map(Reply::into_messages).collect(),
+            reply => vec![reply],
+        }
+    }
+
+    /// Text of a single message for platforms without formatting.
+    ///
+    /// Returns `None` for [`Reply::None`] and [`Reply::Multiple`].
+    pub fn to_plain(&self) -> Option<String> {
+        match self {
+            Self::None | Self::Multiple(_) => None,
+            Self::Text(text) => Some(text.to_owned()),
+            Self::Embed(embed) => Some(embed.to_plain()),
+            Self::File(file) => Some(format!("[file {}, {} bytes]", file.name, file.data.len())),
+        }
+    }
+}
+
+impl From<()> for Reply {
+    fn from(_: ()) -> Self {
+        Self::None
+    }
+}
+
+impl From<String> for Reply {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<&str> for Reply {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_owned())
+    }
+}
+
+impl From<Embed> for Reply {
+    fn from(embed: Embed) -> Self {
+        Self::Embed(embed)
+    }
+}
+
+impl From<Attachment> for Reply {
+    fn from(file: Attachment) -> Self {
+        Self::File(file)
+    }
+}
+
+/// Structured message, rendered as html where possible and as lines of text otherwise.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Embed {
+    pub title: String,
+    /// Link of the title, only shown on platforms with formatting
+    pub url: Option<String>,
+    pub fields: Vec<(String, String)>,
+    pub footer: Option<String>,
+}
+
+impl Embed {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+
+        self
+    }
+
+    pub fn field(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((name.into(), value.to_string()));
+
+        self
+    }
+
+    pub fn footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+
+        self
+    }
+
+    /// The title, one line per field, and the footer.
+    pub fn to_plain(&self) -> String {
+        let mut lines = vec![self.title.clone()];
+
+        for (name, value) in self.fields.iter() {
+            lines.push(format!("{name}: {value}"));
+        }
+
+        lines.extend(self.footer.clone());
+
+        lines.join("\n")
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title);
+
+        let mut html = match self.url {
+            Some(ref url) => format!("<b><a href=\"{}\">{title}</a></b>", escape_html(url)),
+            None => format!("<b>{title}</b>"),
+        };
+
+        for (name, value) in self.fields.iter() {
+            html.push_str(&format!(
+                "<br><b>{}:</b> {}",
+                escape_html(name),
+                escape_html(value)
+            ));
+        }
+
+        if let Some(ref footer) = self.footer {
+            html.push_str(&format!("<br><i>{}</i>", escape_html(footer)));
+        }
+
+        html
+    }
+}
+
+/// File that's uploaded where the platform supports it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attachment {
+    pub name: String,
+    pub content_type: Mime,
+    pub data: Vec<u8>,
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("<br>"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn embed() -> Embed {
+        Embed::new("peppy <3")
+            .url("https://osu.ppy.sh/users/2")
+            .field("Rank", "#1")
+            .footer("cached")
+    }
+
+    #[test]
+    fn embeds_render_plain_and_html() {
+        assert_eq!(embed().to_plain(), "peppy <3\nRank: #1\ncached");
+        assert_eq!(
+            embed().to_html(),
+            "<b><a href=\"https://osu.ppy.sh/users/2\">peppy &lt;3</a></b>\
+             <br><b>Rank:</b> #1<br><i>cached</i>"
+        );
+    }
+
+    #[test]
+    fn multiple_replies_are_flattened() {
+        let reply = Reply::Multiple(vec![
+            Reply::text("a"),
+            Reply::None,
+            Reply::Multiple(vec![embed().into(), Reply::text("b")]),
+        ]);
+
+        let messages = reply.into_messages();
+        let plain: Vec<_> = messages.iter().filter_map(Reply::to_plain).collect();
+
+        assert_eq!(plain, ["a", "peppy <3\nRank: #1\ncached", "b"]);
+        assert!(Reply::from(()).into_messages().is_empty());
+    }
+}
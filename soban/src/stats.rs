@@ -0,0 +1,28 @@
+use std::{collections::HashMap, sync::Mutex};
+
+/// Counts how often each command has been used since startup.
+#[derive(Default)]
+pub struct CommandStats {
+    counts: Mutex<HashMap<&'static str, u64>>,
+}
+
+impl CommandStats {
+    pub fn record(&self, name: &'static str) {
+        *self.counts.lock().unwrap().entry(name).or_default() += 1;
+    }
+
+    /// Command names and their counts, most used first.
+    pub fn counts(&self) -> Vec<(&'static str, u64)> {
+        let mut counts: Vec<_> = self
+            .counts
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, count)| (*name, *count))
+            .collect();
+
+        counts.sort_unstable_by(|(name_a, a), (name_b, b)| b.cmp(a).then(name_a.cmp(name_b)));
+
+        counts
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use eyre::Result;
+use futures::future::BoxFuture;
+
+use crate::{utils::random::random_u64, Context, TASKS_SLICE};
+
+pub(crate) type TaskFn = fn(Arc<Context>, Channels) -> BoxFuture<'static, Result<()>>;
+
+/// Periodic background job, registered through `#[task]`.
+///
+/// Fields are visible to the crate so that the macro can construct it anywhere.
+pub struct Task {
+    pub(crate) name: &'static str,
+    /// Time between the start of two runs
+    pub(crate) every: Duration,
+    /// Maximum random delay added to every interval
+    pub(crate) jitter: Duration,
+    /// `file:line` of the task's definition
+    pub(crate) location: &'static str,
+    pub(crate) run: TaskFn,
+}
+
+/// Channels that subscribed to a task through `!config subscribe`.
+pub struct Channels {
+    task: &'static str,
+    /// Channel keys of the form `platform:channel`
+    keys: Vec<String>,
+}
+
+impl Channels {
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Post the message to every channel.
+    ///
+    /// Failing to post to one channel is logged and doesn't affect the others.
+    pub async fn send(&self, ctx: &Context, msg: &str) {
+        for channel in self.keys.iter() {
+            if let Err(err) = ctx.platforms.send(channel, msg).await {
+                warn!(
+                    task = self.task,
+                    channel,
+                    ?err,
+                    "Failed to post task message"
+                );
+            }
+        }
+    }
+}
+
+/// Spawn every registered task, each on its own schedule.
+pub fn spawn_tasks(ctx: Arc<Context>) {
+    for task in TASKS_SLICE {
+        info!(name = task.name, every = ?task.every, "Scheduling task");
+
+        tokio::spawn(schedule(Arc::clone(&ctx), task));
+    }
+}
+
+/// Run the task forever, skipping runs while the previous one hasn't finished.
+async fn schedule(ctx: Arc<Context>, task: &'static Task) {
+    let running = Arc::new(AtomicBool::new(false));
+
+    loop {
+        tokio::time::sleep(task.every + random_jitter(task.jitter)).await;
+
+        if running.swap(true, Ordering::AcqRel) {
+            warn!(
+                name = task.name,
+                "Skipping task, the previous run is still going"
+            );
+
+            continue;
+        }
+
+        let guard = RunningGuard(Arc::clone(&running));
+        let ctx = Arc::clone(&ctx);
+
+        // spawned so that a panic only ends this run and not the schedule
+        tokio::spawn(async move {
+            let _guard = guard;
+            run_task(ctx, task).await;
+        });
+    }
+}
+
+async fn run_task(ctx: Arc<Context>, task: &'static Task) {
+    let channels = Channels {
+        task: task.name,
+        keys: ctx.db.subscribers(task.name).await,
+    };
+
+    let start = Instant::now();
+
+    match (task.run)(ctx, channels).await {
+        Ok(()) => debug!(name = task.name, elapsed = ?start.elapsed(), "Finished task"),
+        Err(err) => error!(
+            name = task.name,
+            location = task.location,
+            ?err,
+            "Failed to run task"
+        ),
+    }
+}
+
+/// Resets the running flag once a run ends, even if it panicked.
+struct RunningGuard(Arc<AtomicBool>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+
+    Duration::from_millis(random_u64() % (max.as_millis() as u64 + 1))
+}
+
+/// Get a registered task by name.
+pub fn task(name: &str) -> Option<&'static Task> {
+    TASKS_SLICE
+        .iter()
+        .find(|task| task.name.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use eyre::eyre;
+
+    use crate::test_support::{IrcServer, TestContext};
+
+    use super::*;
+
+    fn every_10s(name: &'static str, run: TaskFn) -> Task {
+        Task {
+            name,
+            every: Duration::from_secs(10),
+            jitter: Duration::ZERO,
+            location: concat!(file!(), ":", line!()),
+            run,
+        }
+    }
+
+    async fn run_for(task: Task, secs: u64) {
+        let test = TestContext::new().await;
+        let task = Box::leak(Box::new(task));
+        let handle = tokio::spawn(schedule(Arc::clone(&test.ctx), task));
+
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        handle.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn overlapping_runs_are_skipped() {
+        static STARTED: AtomicUsize = AtomicUsize::new(0);
+
+        fn slow(_: Arc<Context>, _: Channels) -> BoxFuture<'static, Result<()>> {
+            Box::pin(async {
+                STARTED.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(Duration::from_secs(25)).await;
+
+                Ok(())
+            })
+        }
+
+        // starts at 10s and runs until 35s so the runs at 20s and 30s are skipped
+        run_for(every_10s("slow", slow), 45).await;
+        assert_eq!(STARTED.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failing_tasks_stay_scheduled() {
+        static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+        fn failing(_: Arc<Context>, _: Channels) -> BoxFuture<'static, Result<()>> {
+            RUNS.fetch_add(1, Ordering::Relaxed);
+
+            Box::pin(async { Err(eyre!("oops")) })
+        }
+
+        run_for(every_10s("failing", failing), 35).await;
+        assert_eq!(RUNS.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn runs_post_to_subscribers() {
+        fn posting(ctx: Arc<Context>, channels: Channels) -> BoxFuture<'static, Result<()>> {
+            Box::pin(async move {
+                channels.send(&ctx, "hello").await;
+
+                Ok(())
+            })
+        }
+
+        let test = TestContext::new().await;
+        let mut server = IrcServer::start().await;
+        let _ = test.ctx.platforms.irc.set(server.client.sender());
+        let task = Box::leak(Box::new(every_10s("posting", posting)));
+
+        for channel in ["irc:#osu", "irc:#taiko"] {
+            test.ctx
+                .db
+                .update_channel_settings(channel, |settings| {
+                    settings.subscriptions.insert("posting".to_owned())
+                })
+                .await
+                .unwrap();
+        }
+
+        run_task(Arc::clone(&test.ctx), task).await;
+
+        let mut sent = server.next_lines(2).await;
+        sent.sort();
+
+        assert_eq!(sent, ["PRIVMSG #osu hello", "PRIVMSG #taiko hello"]);
+    }
+
+    #[test]
+    fn jitter_is_bounded() {
+        assert_eq!(random_jitter(Duration::ZERO), Duration::ZERO);
+
+        for _ in 0..100 {
+            assert!(random_jitter(Duration::from_millis(50)) <= Duration::from_millis(50));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use soban_macros::task;
+
+use crate::{Channels, Context};
+
+/// Drop expired cache entries and full ratelimit buckets, which are otherwise
+/// only purged once there are a lot of them.
+#[task(every = "10m")]
+async fn purge_expired(ctx: Arc<Context>, _channels: Channels) -> Result<()> {
+    ctx.cache.purge();
+    ctx.ratelimiter.purge();
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use irc::client::{data::Config, Client};
+use tokio::{
+    io::{AsyncBufReadExt, BufReader, Lines},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+use super::MockOrigin;
+
+/// Local irc server with a single connected [`Client`] that records the
+/// lines the client sends.
+pub struct IrcServer {
+    pub client: Client,
+    lines: Lines<BufReader<TcpStream>>,
+}
+
+impl IrcServer {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let config = Config {
+            nickname: Some(MockOrigin::BOT_NAME.to_owned()),
+            server: Some("127.0.0.1".to_owned()),
+            port: Some(listener.local_addr().unwrap().port()),
+            use_tls: Some(false),
+            ..Config::default()
+        };
+
+        let (client, accepted) = tokio::join!(Client::from_config(config), listener.accept());
+        let mut client = client.unwrap();
+
+        // writes queued messages to the connection
+        tokio::spawn(client.outgoing().unwrap());
+
+        Self {
+            client,
+            lines: BufReader::new(accepted.unwrap().0).lines(),
+        }
+    }
+
+    /// Wait for the next lines the client sends, without line endings.
+    pub async fn next_lines(&mut self, count: usize) -> Vec<String> {
+        let mut lines = Vec::with_capacity(count);
+
+        for _ in 0..count {
+            let line = timeout(Duration::from_secs(5), self.lines.next_line())
+                .await
+                .expect("timed out waiting for a line")
+                .unwrap()
+                .expect("connection closed");
+
+            lines.push(line);
+        }
+
+        lines
+    }
+}
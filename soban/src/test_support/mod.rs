@@ -0,0 +1,220 @@
+//! Helpers to run commands offline against a [`MockOrigin`] and a [`StandIn`]
+//! for the osu!api.
+
+mod irc;
+mod origin;
+mod stand_in;
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eyre::Result;
+use serde_json::Value;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+
+use crate::{handle_command, handle_event, CommandOrigin, Context, Database, EventKind};
+
+pub use self::{
+    irc::IrcServer,
+    origin::MockOrigin,
+    stand_in::{LocalOsu, StandIn},
+};
+
+/// User key of the owner of every [`TestContext`]
+pub const OWNER: &str = "irc:owner";
+
+/// Id and name of the user in `user.json`
+pub const USER_ID: u32 = 2;
+pub const USERNAME: &str = "peppy";
+
+/// Id of the map in `map.osu` and `beatmap.json`
+pub const MAP_ID: u32 = 1;
+
+/// A [`Context`] whose osu!api is a [`StandIn`] serving the fixtures and
+/// whose database and map files live in a temporary directory.
+pub struct TestContext {
+    pub ctx: Arc<Context>,
+    pub stand_in: StandIn,
+    /// Removed once the context is dropped
+    _dir: TempDir,
+}
+
+impl TestContext {
+    pub async fn new() -> Self {
+        let dir = TempDir::new();
+        let map_path = dir.path().join("maps");
+        fs::create_dir(&map_path).unwrap();
+
+        let stand_in = StandIn::start().await;
+
+        let user = fixture_json("user.json");
+        stand_in.api(&format!("users/{USER_ID}?key=id"), &user);
+        stand_in.api(&format!("users/{USERNAME}?key=username"), &user);
+
+        let mut scores = fixture_json("recent_scores.json");
+
+        // keep the scores recent regardless of when the tests run
+        for (i, score) in scores.as_array_mut().unwrap().iter_mut().enumerate() {
+            score["ended_at"] = minutes_ago(5 + 30 * i as i64).into();
+        }
+
+        // `recent_scores` requests a single score at an offset
+        for (offset, score) in scores.as_array().unwrap().iter().enumerate() {
+            for include_fails in [0, 1] {
+                let path = format!(
+                    "users/{USER_ID}/scores/recent?limit=1&offset={offset}&include_fails={include_fails}"
+                );
+
+                stand_in.api(&path, &Value::Array(vec![score.clone()]));
+            }
+        }
+
+        // `!recentlist` requests all of them at once
+        for include_fails in [0, 1] {
+            let path = format!(
+                "users/{USER_ID}/scores/recent?limit=50&offset=0&include_fails={include_fails}"
+            );
+
+            stand_in.api(&path, &scores);
+        }
+
+        stand_in.api(
+            &format!("users/{USER_ID}/scores/recent"),
+            &Value::Array(Vec::new()),
+        );
+        stand_in.api(
+            &format!("users/{USER_ID}/scores/best?limit=100"),
+            &fixture_json("best_scores.json"),
+        );
+        stand_in.api(
+            "rankings/osu/performance?cursor[page]=1",
+            &ranking_page(&[8000.0, 7700.0, 7000.0], 3),
+        );
+        stand_in.api(
+            &format!("beatmaps/lookup?id={MAP_ID}"),
+            &fixture_json("beatmap.json"),
+        );
+        stand_in.route(&format!("/osu/{MAP_ID}"), 200, fixture("map.osu"));
+
+        let db = Database::open(dir.path().join("db.json")).await.unwrap();
+        let osu = Box::new(LocalOsu::new(stand_in.base_url()));
+        let owners = HashSet::from([OWNER.to_owned()]);
+        let ctx = Arc::new(Context::new(osu, db, owners, map_path));
+
+        Self {
+            ctx,
+            stand_in,
+            _dir: dir,
+        }
+    }
+
+    /// Handle the message as if `origin` sent it.
+    pub async fn run(&self, origin: &MockOrigin, msg: &str) -> Result<()> {
+        handle_command(Arc::clone(&self.ctx), CommandOrigin::new(origin), msg).await
+    }
+
+    /// Handle the event as if `origin` caused it, passing it to listeners.
+    pub async fn event(&self, origin: &MockOrigin, kind: EventKind, text: &str) -> Result<()> {
+        handle_event(
+            Arc::clone(&self.ctx),
+            CommandOrigin::new(origin),
+            kind,
+            text,
+        )
+        .await
+    }
+}
+
+pub fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+
+    fs::read_to_string(&path).unwrap_or_else(|err| panic!("failed to read {path:?}: {err}"))
+}
+
+pub fn fixture_json(name: &str) -> Value {
+    serde_json::from_str(&fixture(name)).unwrap()
+}
+
+/// Page of the pp leaderboard holding users with the given pp, made from
+/// `user.json`, for a leaderboard of `total` users.
+pub fn ranking_page(pp: &[f64], total: u32) -> Value {
+    let mut user = fixture_json("user.json");
+    let stats = user.as_object_mut().unwrap().remove("statistics").unwrap();
+
+    let ranking: Vec<Value> = pp
+        .iter()
+        .map(|&pp| {
+            let mut entry = stats.clone();
+            entry["pp"] = pp.into();
+            entry["user"] = user.clone();
+
+            entry
+        })
+        .collect();
+
+    serde_json::json!({ "cursor": null, "ranking": ranking, "total": total })
+}
+
+/// Rfc3339 timestamp of the given amount of minutes ago, in whole seconds
+/// since the api doesn't send fractions.
+pub fn minutes_ago(minutes: i64) -> String {
+    (OffsetDateTime::now_utc() - Duration::minutes(minutes))
+        .replace_nanosecond(0)
+        .unwrap()
+        .format(&Rfc3339)
+        .unwrap()
+}
+
+/// Directory that's removed once dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let name = format!(
+            "soban-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        );
+
+        let path = std::env::temp_dir().join(name);
+        fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rosu_v2::prelude::{Beatmap, Score, User};
+
+    use super::*;
+
+    #[test]
+    fn fixtures_deserialize() {
+        serde_json::from_str::<User>(&fixture("user.json")).unwrap();
+        serde_json::from_str::<Beatmap>(&fixture("beatmap.json")).unwrap();
+        serde_json::from_str::<Vec<Score>>(&fixture("recent_scores.json")).unwrap();
+        rosu_pp::Beatmap::from_bytes(fixture("map.osu").as_bytes()).unwrap();
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::Mutex;
+
+use eyre::Result;
+use futures::{future::BoxFuture, FutureExt};
+
+use crate::{Author, Origin, Platform, Reply};
+
+/// Command origin that records every message sent to it instead of
+/// forwarding it to a chat service.
+pub struct MockOrigin {
+    pub platform: Platform,
+    pub channel: String,
+    pub user: String,
+    /// Whether the user counts as moderator of the channel
+    pub moderator: bool,
+    sent: Mutex<Vec<String>>,
+    /// Replies of commands before they're rendered
+    replies: Mutex<Vec<Reply>>,
+}
+
+impl MockOrigin {
+    pub const BOT_NAME: &'static str = "soban";
+
+    pub fn new(user: &str) -> Self {
+        Self {
+            platform: Platform::Irc,
+            channel: "#test".to_owned(),
+            user: user.to_owned(),
+            moderator: false,
+            sent: Mutex::new(Vec::new()),
+            replies: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn record(&self, msg: &str) {
+        self.sent.lock().unwrap().push(msg.to_owned());
+    }
+
+    /// Record the reply and its plain text as sent message.
+    pub fn record_reply(&self, reply: Reply) {
+        if let Some(text) = reply.to_plain() {
+            self.record(&text);
+        }
+
+        self.replies.lock().unwrap().push(reply);
+    }
+
+    /// Remove and return all replies recorded so far.
+    pub fn take_replies(&self) -> Vec<Reply> {
+        std::mem::take(&mut *self.replies.lock().unwrap())
+    }
+
+    /// Remove and return all messages recorded so far.
+    pub fn take_sent(&self) -> Vec<String> {
+        std::mem::take(&mut *self.sent.lock().unwrap())
+    }
+
+    /// Remove all recorded messages and return the only one.
+    ///
+    /// Panics if there wasn't exactly one message.
+    #[track_caller]
+    pub fn take_single(&self) -> String {
+        let mut sent = self.take_sent();
+        assert_eq!(sent.len(), 1, "expected a single message, got {sent:?}");
+
+        sent.remove(0)
+    }
+}
+
+impl Origin for MockOrigin {
+    fn send<'a>(&'a self, msg: &'a str) -> BoxFuture<'a, Result<()>> {
+        self.record(msg);
+
+        async { Ok(()) }.boxed()
+    }
+
+    fn send_reply(&self, reply: Reply) -> BoxFuture<'_, Result<()>> {
+        self.record_reply(reply);
+
+        async { Ok(()) }.boxed()
+    }
+
+    fn bot_name(&self) -> &str {
+        Self::BOT_NAME
+    }
+
+    fn is_from_bot(&self) -> bool {
+        self.user == Self::BOT_NAME
+    }
+
+    fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    fn author(&self) -> Author<'_> {
+        Author {
+            platform: self.platform,
+            id: &self.user,
+            name: &self.user,
+            account: Some(&self.user),
+        }
+    }
+
+    fn is_channel_moderator(&self) -> BoxFuture<'_, Result<bool>> {
+        let moderator = self.moderator;
+
+        async move { Ok(moderator) }.boxed()
+    }
+}
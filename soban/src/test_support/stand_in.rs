@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use futures::{future::BoxFuture, FutureExt};
+use rosu_v2::{
+    prelude::{Beatmap, OsuError, OsuResult, Rankings, Score, StatusCode, User},
+    request::UserId,
+};
+use serde::de::DeserializeOwned;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use crate::OsuApi;
+
+/// Local http server standing in for the osu!api and `/osu/{id}` downloads.
+///
+/// Responses are registered per path. A path including the query is matched
+/// first, then the path without query. Unknown paths respond with a 404.
+pub struct StandIn {
+    addr: SocketAddr,
+    state: Arc<State>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct State {
+    routes: Mutex<HashMap<String, (u16, Vec<u8>)>>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl StandIn {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(State::default());
+
+        let task_state = Arc::clone(&state);
+
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(handle_connection(stream, Arc::clone(&task_state)));
+            }
+        });
+
+        Self { addr, state, task }
+    }
+
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn route(&self, path: &str, status: u16, body: impl Into<Vec<u8>>) {
+        let mut routes = self.state.routes.lock().unwrap();
+        routes.insert(path.to_owned(), (status, body.into()));
+    }
+
+    /// Respond to an osu!api path like `users/2?key=id` with the given json.
+    pub fn api(&self, path: &str, body: &serde_json::Value) {
+        self.route(&format!("/api/v2/{path}"), 200, body.to_string());
+    }
+
+    /// All requested paths in order, including their query.
+    pub fn requests(&self) -> Vec<String> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for StandIn {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<State>) {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+
+    // requests are only GETs without body so reading the head suffices
+    while !buf.windows(4).any(|window| window == b"\r\n\r\n") {
+        match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => buf.extend_from_slice(&chunk[..n]),
+        }
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let path = head.split(' ').nth(1).unwrap_or("/").to_owned();
+    state.requests.lock().unwrap().push(path.clone());
+
+    let (status, body) = {
+        let routes = state.routes.lock().unwrap();
+        let without_query = path.split('?').next().unwrap_or(&path);
+
+        routes
+            .get(&path)
+            .or_else(|| routes.get(without_query))
+            .cloned()
+            .unwrap_or_else(|| (404, br#"{"error":null}"#.to_vec()))
+    };
+
+    let head = format!(
+        "HTTP/1.1 {status} Stand-In\r\ncontent-length: {}\r\ncontent-type: application/json\r\nconnection: close\r\n\r\n",
+        body.len()
+    );
+
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(&body).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Talks to a [`StandIn`] the same way [`rosu_v2::Osu`] talks to the osu!api,
+/// requesting the same paths and queries.
+pub struct LocalOsu {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl LocalOsu {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url,
+        }
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> OsuResult<T> {
+        let url = format!("{}/api/v2/{path}", self.base_url);
+
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|err| OsuError::ServiceUnavailable(err.to_string()))?;
+
+        let status = response.status().as_u16();
+
+        let body = response
+            .text()
+            .await
+            .map_err(|err| OsuError::ServiceUnavailable(err.to_string()))?;
+
+        match status {
+            200..=299 => {
+                serde_json::from_str(&body).map_err(|source| OsuError::Parsing { body, source })
+            }
+            404 => Err(OsuError::NotFound),
+            503 => Err(OsuError::ServiceUnavailable(body)),
+            _ => Err(OsuError::Response {
+                source: serde_json::from_str(&body)
+                    .unwrap_or(rosu_v2::error::ApiError { error: None }),
+                body,
+                status: StatusCode::from_u16(status).unwrap(),
+            }),
+        }
+    }
+}
+
+impl OsuApi for LocalOsu {
+    fn user(&self, user_id: UserId) -> BoxFuture<'_, OsuResult<User>> {
+        let path = match user_id {
+            UserId::Id(id) => format!("users/{id}?key=id"),
+            UserId::Name(name) => format!("users/{name}?key=username"),
+        };
+
+        async move { self.get(&path).await }.boxed()
+    }
+
+    fn recent_scores(
+        &self,
+        user_id: u32,
+        include_fails: bool,
+        offset: usize,
+        limit: usize,
+    ) -> BoxFuture<'_, OsuResult<Vec<Score>>> {
+        let path = format!(
+            "users/{user_id}/scores/recent?limit={limit}&offset={offset}&include_fails={}",
+            include_fails as u8
+        );
+
+        async move { self.get(&path).await }.boxed()
+    }
+
+    fn best_scores(&self, user_id: u32, limit: usize) -> BoxFuture<'_, OsuResult<Vec<Score>>> {
+        let path = format!("users/{user_id}/scores/best?limit={limit}");
+
+        async move { self.get(&path).await }.boxed()
+    }
+
+    fn performance_rankings<'a>(
+        &'a self,
+        country: Option<&'a str>,
+        page: u32,
+    ) -> BoxFuture<'a, OsuResult<Rankings>> {
+        let path = match country {
+            Some(country) => {
+                format!("rankings/osu/performance?country={country}&cursor[page]={page}")
+            }
+            None => format!("rankings/osu/performance?cursor[page]={page}"),
+        };
+
+        async move { self.get(&path).await }.boxed()
+    }
+
+    fn beatmap(&self, map_id: u32) -> BoxFuture<'_, OsuResult<Beatmap>> {
+        async move { self.get(&format!("beatmaps/lookup?id={map_id}")).await }.boxed()
+    }
+
+    fn osu_file_url(&self, map_id: u32) -> String {
+        format!("{}/osu/{map_id}", self.base_url)
+    }
+}
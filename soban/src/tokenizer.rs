@@ -0,0 +1,256 @@
+//! Splitting messages into command names and arguments.
+//!
+//! Chat clients, mobile ones in particular, don't always send plain spaces,
+//! so every Unicode whitespace character as well as the invisible zero-width
+//! ones separate words.
+
+/// Whether the character separates words.
+pub fn is_separator(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// Split off the first word, returning it and the trimmed remainder.
+pub fn split_word(input: &str) -> (&str, &str) {
+    let input = input.trim_matches(is_separator);
+
+    match input.find(is_separator) {
+        Some(idx) => (&input[..idx], input[idx..].trim_start_matches(is_separator)),
+        None => (input, ""),
+    }
+}
+
+/// Split a message without prefix into the command name, an optional number
+/// suffix of the name like in `rs3`, and the remaining arguments.
+///
+/// The suffix is the trailing run of digits, ASCII or full-width, of a name
+/// that otherwise ends in a letter. It's split off even if it doesn't fit into
+/// a `u32`, in which case the number is `None`.
+pub fn split_command(msg: &str) -> (&str, Option<u32>, &str) {
+    let (word, rest) = split_word(msg);
+
+    let name = word.trim_end_matches(|c| digit(c).is_some());
+
+    if name.len() == word.len() || !name.chars().next_back().is_some_and(char::is_alphabetic) {
+        return (word, None, rest);
+    }
+
+    let num = word[name.len()..]
+        .chars()
+        .filter_map(digit)
+        .try_fold(0_u32, |num, digit| num.checked_mul(10)?.checked_add(digit));
+
+    (name, num, rest)
+}
+
+fn digit(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => c.to_digit(10),
+        '０'..='９' => Some(c as u32 - '０' as u32),
+        _ => None,
+    }
+}
+
+/// Strip the prefix from the start of the message, ignoring case and leading
+/// whitespace.
+pub fn strip_prefix<'m>(msg: &'m str, prefix: &str) -> Option<&'m str> {
+    let msg = msg.trim_start_matches(is_separator);
+    let start = msg.get(..prefix.len())?;
+
+    start
+        .eq_ignore_ascii_case(prefix)
+        .then(|| &msg[prefix.len()..])
+}
+
+/// Strip a leading mention of the bot like `soban: ` or `@soban `.
+pub fn strip_mention<'m>(bot_name: &str, msg: &'m str) -> Option<&'m str> {
+    let msg = msg.trim_start_matches(is_separator);
+
+    let (msg, at) = match msg.strip_prefix('@') {
+        Some(msg) => (msg, true),
+        None => (msg, false),
+    };
+
+    let name = msg.get(..bot_name.len())?;
+
+    if !name.eq_ignore_ascii_case(bot_name) {
+        return None;
+    }
+
+    let rest = &msg[bot_name.len()..];
+
+    let rest = match rest.strip_prefix([':', ',']) {
+        Some(rest) => rest,
+        None if at && rest.starts_with(is_separator) => rest,
+        None => return None,
+    };
+
+    Some(rest.trim_start_matches(is_separator)).filter(|rest| !rest.is_empty())
+}
+
+/// A word of the arguments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    /// Quoted words are always taken literally, never as flags or other syntax
+    pub quoted: bool,
+}
+
+impl PartialEq<&str> for Token {
+    fn eq(&self, other: &&str) -> bool {
+        self.text == *other
+    }
+}
+
+/// Split arguments into words, keeping quoted parts like `"some user"`
+/// together.
+///
+/// Straight and curly double quotes are recognized. Whitespace inside quotes
+/// is collapsed into single spaces, a missing closing quote extends the
+/// quoted part to the end of the input, and empty words are dropped.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim_start_matches(is_separator);
+
+    while !rest.is_empty() {
+        let quoted = rest.starts_with(is_open_quote);
+
+        let (text, next) = match rest.strip_prefix(is_open_quote) {
+            Some(quoted) => {
+                let end = quoted.find(is_close_quote).unwrap_or(quoted.len());
+                let (inner, next) = quoted.split_at(end);
+                let inner = inner.split(is_separator).filter(|word| !word.is_empty());
+
+                (
+                    inner.collect::<Vec<_>>().join(" "),
+                    next.strip_prefix(is_close_quote),
+                )
+            }
+            None => {
+                let end = rest.find(is_separator).unwrap_or(rest.len());
+
+                (rest[..end].to_owned(), Some(&rest[end..]))
+            }
+        };
+
+        if !text.is_empty() {
+            tokens.push(Token { text, quoted });
+        }
+
+        rest = next.unwrap_or_default().trim_start_matches(is_separator);
+    }
+
+    tokens
+}
+
+fn is_open_quote(c: char) -> bool {
+    matches!(c, '"' | '“' | '”' | '„')
+}
+
+fn is_close_quote(c: char) -> bool {
+    matches!(c, '"' | '“' | '”')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_command_without_number() {
+        assert_eq!(split_command("rs"), ("rs", None, ""));
+        assert_eq!(split_command("rs peppy"), ("rs", None, "peppy"));
+        assert_eq!(split_command("osu some user"), ("osu", None, "some user"));
+    }
+
+    #[test]
+    fn split_command_with_number() {
+        assert_eq!(split_command("rs3"), ("rs", Some(3), ""));
+        assert_eq!(split_command("rs12 peppy"), ("rs", Some(12), "peppy"));
+        assert_eq!(split_command("rs03"), ("rs", Some(3), ""));
+        assert_eq!(split_command("rs0"), ("rs", Some(0), ""));
+        assert_eq!(split_command("RS3"), ("RS", Some(3), ""));
+    }
+
+    #[test]
+    fn split_command_invalid_number() {
+        // the suffix is split off even if it doesn't fit into a u32
+        assert_eq!(split_command("rs99999999999"), ("rs", None, ""));
+
+        // names made of digits only don't have a suffix
+        assert_eq!(split_command("123"), ("123", None, ""));
+
+        // neither do names whose suffix doesn't follow a letter
+        assert_eq!(split_command("rs_3"), ("rs_3", None, ""));
+    }
+
+    #[test]
+    fn split_command_odd_whitespace() {
+        assert_eq!(split_command("rs  \t peppy "), ("rs", None, "peppy"));
+        assert_eq!(split_command("rs\u{3000}peppy"), ("rs", None, "peppy"));
+        assert_eq!(split_command("rs\u{A0}peppy"), ("rs", None, "peppy"));
+        assert_eq!(split_command("rs\u{200B}peppy"), ("rs", None, "peppy"));
+        assert_eq!(
+            split_command("osu some\u{3000}user"),
+            ("osu", None, "some\u{3000}user")
+        );
+    }
+
+    #[test]
+    fn split_command_unicode() {
+        assert_eq!(split_command("rs３"), ("rs", Some(3), ""));
+        assert_eq!(split_command("rs１２ peppy"), ("rs", Some(12), "peppy"));
+        assert_eq!(split_command("ранг2"), ("ранг", Some(2), ""));
+        assert_eq!(split_command("ß"), ("ß", None, ""));
+    }
+
+    #[test]
+    fn strip_prefix_ignores_case() {
+        assert_eq!(strip_prefix("!rs", "!"), Some("rs"));
+        assert_eq!(strip_prefix(" \u{3000}!rs", "!"), Some("rs"));
+        assert_eq!(strip_prefix("S!rs", "s!"), Some("rs"));
+        assert_eq!(strip_prefix("?rs", "!"), None);
+        assert_eq!(strip_prefix("é", "!!"), None);
+    }
+
+    #[test]
+    fn strip_mention_variants() {
+        assert_eq!(strip_mention("soban", "soban: rs"), Some("rs"));
+        assert_eq!(strip_mention("soban", "Soban, rs peppy"), Some("rs peppy"));
+        assert_eq!(strip_mention("soban", "@soban rs"), Some("rs"));
+        assert_eq!(strip_mention("soban", "@soban: rs"), Some("rs"));
+        assert_eq!(strip_mention("soban", "@soban\u{3000}rs"), Some("rs"));
+        assert_eq!(strip_mention("soban", "soban:\u{3000}rs"), Some("rs"));
+        assert_eq!(strip_mention("soban", "soban rs"), None);
+        assert_eq!(strip_mention("soban", "soban:"), None);
+        assert_eq!(strip_mention("soban", "sobanbot: rs"), None);
+        assert_eq!(strip_mention("soban", "@ soban rs"), None);
+    }
+
+    #[test]
+    fn tokenize_words() {
+        assert!(tokenize("").is_empty());
+        assert!(tokenize(" \u{3000} ").is_empty());
+        assert_eq!(tokenize("some user"), ["some", "user"]);
+        assert_eq!(tokenize("  some \t\u{3000}user\u{200B}"), ["some", "user"]);
+    }
+
+    #[test]
+    fn tokenize_quotes() {
+        assert_eq!(tokenize("\"some user\""), ["some user"]);
+        assert_eq!(tokenize("\"some user\" --fresh"), ["some user", "--fresh"]);
+        assert_eq!(tokenize("“some user” --fresh"), ["some user", "--fresh"]);
+        assert_eq!(tokenize("\"  some \u{3000} user \""), ["some user"]);
+        assert_eq!(tokenize("\"a b\"\"c d\""), ["a b", "c d"]);
+
+        // missing closing quote
+        assert_eq!(tokenize("\"some user"), ["some user"]);
+
+        // empty quotes
+        assert_eq!(tokenize("\"\" peppy"), ["peppy"]);
+
+        // quotes inside a word are kept
+        assert_eq!(tokenize("a\"b c"), ["a\"b", "c"]);
+
+        let quoted = |input| tokenize(input).iter().map(|t| t.quoted).collect::<Vec<_>>();
+        assert_eq!(quoted("\"--fresh\" --fresh"), [true, false]);
+    }
+}
@@ -1,21 +1,274 @@
+use std::{fmt, sync::LazyLock};
+
 use eyre::Result;
-use reqwest;
+use regex::Regex;
 use rosu_pp::Beatmap;
-use std::{env, fs::File, io::Write, path::Path};
+use rosu_v2::prelude::GameMods;
+use tokio::fs;
+
+use crate::{utils::random::random_u64, CommandError, Context};
+
+/// Parse the `.osu` file of a map, downloading it first if it's not stored yet.
+///
+/// Downloads are only stored once they parse, since unknown maps respond
+/// with an empty body that would otherwise be cached for good.
+pub async fn get_beatmap(ctx: &Context, id: u32) -> Result<Beatmap> {
+    let path = ctx.map_path.join(format!("{id}.osu"));
 
-pub async fn get_beatmap(id: u32) -> Result<Beatmap> {
-    let dir = env::var("OSU_MAP_PATH")?;
-    let osu_path = format!("{dir}{id}.osu");
-    let path = Path::new(&osu_path);
     if path.exists() {
-        Ok(Beatmap::from_path(path)?)
-    } else {
-        let response = reqwest::get(format!("https://osu.ppy.sh/osu/{id}"))
+        return Ok(Beatmap::from_path(path)?);
+    }
+
+    let download = async {
+        reqwest::get(ctx.osu.osu_file_url(id))
             .await?
-            .text()
-            .await?;
-        let mut file = File::create(&format!("{dir}{id}.osu"))?;
-        file.write_all(response.as_bytes())?;
-        Ok(Beatmap::from_path(path)?)
+            .error_for_status()?
+            .bytes()
+            .await
+    };
+
+    let bytes = download
+        .await
+        .map_err(|err| CommandError::upstream("osu!", err))?;
+
+    if bytes.is_empty() {
+        return Err(CommandError::user("unknown map").into());
+    }
+
+    let map = Beatmap::from_bytes(&bytes).map_err(|err| CommandError::upstream("osu!", err))?;
+
+    // written under a unique name first so that concurrent requests for
+    // the same map never parse a partially written file
+    let tmp_path = path.with_extension(format!("osu.{:x}", random_u64()));
+    fs::write(&tmp_path, bytes).await?;
+    fs::rename(&tmp_path, &path).await?;
+
+    Ok(map)
+}
+
+/// Link to a map like `osu.ppy.sh/b/123` anywhere in a text, capturing the map id
+pub static MAP_LINK_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"osu\.ppy\.sh/(?:b|beatmaps|beatmapsets/\d+#\w+)/(\d+)").unwrap());
+
+/// Map id of a link like `https://osu.ppy.sh/b/123` or a plain id.
+pub fn parse_map_id(input: &str) -> Option<u32> {
+    let id = match MAP_LINK_REGEX.captures(input) {
+        // the link has to be the whole input, apart from the scheme and a
+        // query or fragment
+        Some(captures) => {
+            let link = captures.get(0)?;
+
+            if !matches!(&input[..link.start()], "" | "http://" | "https://") {
+                return None;
+            }
+
+            let rest = &input[link.end()..];
+
+            if !(rest.is_empty() || rest.starts_with(['?', '#'])) {
+                return None;
+            }
+
+            captures.get(1)?.as_str()
+        }
+        None => input,
+    };
+
+    id.parse().ok()
+}
+
+/// Name of a map like `Artist - Title [Version]`.
+pub fn map_name(map: &rosu_v2::prelude::Beatmap) -> String {
+    match map.mapset {
+        Some(ref mapset) => format!("{} - {} [{}]", mapset.artist, mapset.title, map.version),
+        None => format!("[{}]", map.version),
+    }
+}
+
+/// Difficulty settings, BPM and length of a map with mods and the clock rate
+/// applied, displayed like `CS5.2 AR10.33 OD10 HP7`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MapAttributes {
+    pub cs: f64,
+    pub ar: f64,
+    pub od: f64,
+    pub hp: f64,
+    pub bpm: f64,
+    /// Drain time in seconds
+    pub drain: f64,
+    pub clock_rate: f64,
+}
+
+impl MapAttributes {
+    /// Attributes of the map with the mods, where `clock_rate` overrides the
+    /// one of the mods.
+    ///
+    /// The drain time is taken from the map's api info since the parsed map
+    /// doesn't know when sliders end.
+    pub fn new(
+        map: &Beatmap,
+        info: &rosu_v2::prelude::Beatmap,
+        mods: GameMods,
+        clock_rate: Option<f64>,
+    ) -> Self {
+        let mut builder = map.attributes();
+        builder.mods(mods.bits());
+
+        if let Some(clock_rate) = clock_rate {
+            builder.clock_rate(clock_rate);
+        }
+
+        let attrs = builder.build();
+
+        Self {
+            cs: attrs.cs,
+            ar: attrs.ar,
+            od: attrs.od,
+            hp: attrs.hp,
+            bpm: map.bpm() * attrs.clock_rate,
+            drain: f64::from(info.seconds_drain) / attrs.clock_rate,
+            clock_rate: attrs.clock_rate,
+        }
+    }
+
+    /// Attributes without mods straight from the map's api info, so that the
+    /// `.osu` file isn't needed.
+    pub fn from_info(info: &rosu_v2::prelude::Beatmap) -> Self {
+        Self {
+            cs: f64::from(info.cs),
+            ar: f64::from(info.ar),
+            od: f64::from(info.od),
+            hp: f64::from(info.hp),
+            bpm: f64::from(info.bpm),
+            drain: f64::from(info.seconds_drain),
+            clock_rate: 1.0,
+        }
+    }
+
+    /// Drain time like `3:05`.
+    pub fn length(&self) -> String {
+        let secs = self.drain.round() as u32;
+
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+impl fmt::Display for MapAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // rounded so that whole values don't show decimals
+        let round = |value: f64| (value * 100.0).round() / 100.0;
+
+        write!(
+            f,
+            "CS{} AR{} OD{} HP{}",
+            round(self.cs),
+            round(self.ar),
+            round(self.od),
+            round(self.hp)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{fixture, fixture_json, TestContext, MAP_ID};
+
+    use super::*;
+
+    #[test]
+    fn parses_map_ids() {
+        assert_eq!(parse_map_id("123"), Some(123));
+        assert_eq!(parse_map_id("https://osu.ppy.sh/b/123"), Some(123));
+        assert_eq!(parse_map_id("osu.ppy.sh/beatmaps/123?mode=osu"), Some(123));
+        assert_eq!(
+            parse_map_id("https://osu.ppy.sh/beatmapsets/1#osu/123"),
+            Some(123)
+        );
+        assert_eq!(parse_map_id("https://osu.ppy.sh/users/123"), None);
+        assert_eq!(parse_map_id("peppy"), None);
+        assert_eq!(parse_map_id("https://osu.ppy.sh/b/123abc"), None);
+        assert_eq!(parse_map_id("osu.ppy.sh/b/123/garbage"), None);
+        assert_eq!(parse_map_id("see osu.ppy.sh/b/123"), None);
+    }
+
+    #[tokio::test]
+    async fn invalid_downloads_arent_stored() {
+        let test = TestContext::new().await;
+        test.stand_in.route("/osu/2", 200, "");
+        test.stand_in.route("/osu/3", 200, "not a map");
+
+        let err = get_beatmap(&test.ctx, 2).await.unwrap_err();
+        assert!(matches!(CommandError::from(err), CommandError::User(msg) if msg == "unknown map"));
+
+        let err = get_beatmap(&test.ctx, 3).await.unwrap_err();
+        assert!(matches!(
+            CommandError::from(err),
+            CommandError::Upstream { .. }
+        ));
+
+        assert!(!test.ctx.map_path.join("2.osu").exists());
+        assert!(!test.ctx.map_path.join("3.osu").exists());
+
+        get_beatmap(&test.ctx, MAP_ID).await.unwrap();
+        assert!(test.ctx.map_path.join(format!("{MAP_ID}.osu")).exists());
+    }
+
+    fn fixture_map() -> (Beatmap, rosu_v2::prelude::Beatmap) {
+        let map = Beatmap::from_bytes(fixture("map.osu").as_bytes()).unwrap();
+        let info = serde_json::from_value(fixture_json("beatmap.json")).unwrap();
+
+        (map, info)
+    }
+
+    fn attributes(mods: GameMods, clock_rate: Option<f64>) -> MapAttributes {
+        let (map, info) = fixture_map();
+
+        MapAttributes::new(&map, &info, mods, clock_rate)
+    }
+
+    #[test]
+    fn nomod_attributes() {
+        let attrs = attributes(GameMods::NoMod, None);
+
+        assert_eq!(attrs.to_string(), "CS4 AR9 OD8 HP5");
+        assert_eq!(attrs.bpm, 200.0);
+        assert_eq!(attrs.length(), "0:10");
+    }
+
+    #[test]
+    fn mods_adjust_attributes() {
+        let attrs = attributes(GameMods::HardRock | GameMods::DoubleTime, None);
+
+        assert_eq!(attrs.to_string(), "CS5.2 AR11 OD11.11 HP7");
+        assert_eq!(attrs.bpm, 300.0);
+        assert_eq!(attrs.length(), "0:07");
+
+        let attrs = attributes(GameMods::Easy | GameMods::HalfTime, None);
+
+        assert_eq!(attrs.to_string(), "CS2 AR1 OD0.89 HP2.5");
+        assert_eq!(attrs.bpm, 150.0);
+        assert_eq!(attrs.length(), "0:13");
+    }
+
+    #[test]
+    fn attributes_are_clamped() {
+        let (map, info) = fixture_map();
+        let map = Beatmap {
+            cs: 9.0,
+            hp: 8.0,
+            ..map
+        };
+        let attrs = MapAttributes::new(&map, &info, GameMods::HardRock, None);
+
+        assert_eq!(attrs.cs, 10.0);
+        assert_eq!(attrs.hp, 10.0);
+    }
+
+    #[test]
+    fn custom_clock_rates() {
+        let attrs = attributes(GameMods::DoubleTime, Some(1.2));
+
+        assert_eq!(attrs.clock_rate, 1.2);
+        assert_eq!(attrs.bpm, 240.0);
+        assert_eq!(attrs.to_string(), "CS4 AR9.67 OD8.89 HP5");
     }
 }
@@ -0,0 +1,255 @@
+//! Performance calculation with all of rosu-pp's knobs.
+
+use std::{ops::RangeInclusive, str::FromStr};
+
+use eyre::Result;
+use rosu_pp::{osu::OsuPerformanceAttributes, AnyPP, Beatmap, BeatmapExt};
+use rosu_v2::prelude::{GameMods, Score};
+
+use crate::{tokenizer::Token, utils::beatmap::parse_map_id, CommandError};
+
+/// Clock rates that can be simulated, from half to double speed
+const CLOCK_RATES: RangeInclusive<f64> = 0.5..=2.0;
+
+/// A score to calculate, anything that's not set is assumed to be the best
+/// possible.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreParams {
+    pub mods: GameMods,
+    /// Accuracy in percent
+    pub accuracy: Option<f64>,
+    pub combo: Option<usize>,
+    pub n300: Option<usize>,
+    pub n100: Option<usize>,
+    pub n50: Option<usize>,
+    pub misses: Option<usize>,
+    /// Amount of objects played for partial plays like fails
+    pub passed_objects: Option<usize>,
+    /// Overrides the clock rate of the mods
+    pub clock_rate: Option<f64>,
+}
+
+impl ScoreParams {
+    /// Mods and hit results of a score from the api.
+    pub fn from_score(score: &Score) -> Self {
+        let stats = &score.statistics;
+
+        Self {
+            mods: score.mods,
+            accuracy: None,
+            combo: Some(score.max_combo as usize),
+            n300: Some(stats.count_300 as usize),
+            n100: Some(stats.count_100 as usize),
+            n50: Some(stats.count_50 as usize),
+            misses: Some(stats.count_miss as usize),
+            passed_objects: None,
+            clock_rate: None,
+        }
+    }
+
+    /// Performance calculator for the map with every set knob applied.
+    pub fn performance<'m>(&self, map: &'m Beatmap) -> AnyPP<'m> {
+        let mut pp = map.pp().mods(self.mods.bits());
+
+        if let Some(accuracy) = self.accuracy {
+            pp = pp.accuracy(accuracy);
+        }
+
+        if let Some(combo) = self.combo {
+            pp = pp.combo(combo);
+        }
+
+        if let Some(n300) = self.n300 {
+            pp = pp.n300(n300);
+        }
+
+        if let Some(n100) = self.n100 {
+            pp = pp.n100(n100);
+        }
+
+        if let Some(n50) = self.n50 {
+            pp = pp.n50(n50);
+        }
+
+        if let Some(misses) = self.misses {
+            pp = pp.n_misses(misses);
+        }
+
+        if let Some(passed_objects) = self.passed_objects {
+            pp = pp.passed_objects(passed_objects);
+        }
+
+        if let Some(clock_rate) = self.clock_rate {
+            pp = pp.clock_rate(clock_rate);
+        }
+
+        pp
+    }
+
+    /// Parse simulation arguments like `456 +DT 99.1% 1200x 2m 5x100`, which
+    /// have to include a map id or link.
+    ///
+    /// Hit results are given as `5x100`, `3x50` or `1000x300`, misses as `2m`
+    /// or `2miss`, the combo as `1200x`, and custom clock rates and partial
+    /// plays as `rate=1.2` and `passed=500`.
+    pub fn parse_simulation(tokens: &[Token]) -> Result<(u32, Self)> {
+        let mut map_id = None;
+        let mut params = Self::default();
+
+        for token in tokens {
+            let word = token.text.to_ascii_lowercase();
+
+            let parsed = if let Some(mods) = word.strip_prefix('+') {
+                GameMods::from_str(mods).map(|mods| params.mods = mods).ok()
+            } else if let Some(acc) = word.strip_suffix('%') {
+                parse_in(acc, 0.0..=100.0).map(|acc| params.accuracy = Some(acc))
+            } else if let Some(rate) = word.strip_prefix("rate=") {
+                parse_in(rate, CLOCK_RATES).map(|rate| params.clock_rate = Some(rate))
+            } else if let Some(passed) = word.strip_prefix("passed=") {
+                passed.parse().ok().map(|n| params.passed_objects = Some(n))
+            } else if let Some((count, result)) =
+                word.split_once('x').filter(|(_, r)| !r.is_empty())
+            {
+                let count = count.parse().ok();
+
+                match result {
+                    "300" => count.map(|n| params.n300 = Some(n)),
+                    "100" => count.map(|n| params.n100 = Some(n)),
+                    "50" => count.map(|n| params.n50 = Some(n)),
+                    _ => None,
+                }
+            } else if let Some(combo) = word.strip_suffix('x') {
+                combo.parse().ok().map(|combo| params.combo = Some(combo))
+            } else if let Some(misses) =
+                word.strip_suffix("miss").or_else(|| word.strip_suffix('m'))
+            {
+                misses.parse().ok().map(|n| params.misses = Some(n))
+            } else if map_id.is_none() {
+                parse_map_id(&token.text).map(|id| map_id = Some(id))
+            } else {
+                None
+            };
+
+            if parsed.is_none() {
+                let msg = format!("couldn't understand `{}`", token.text);
+
+                return Err(CommandError::user(msg).into());
+            }
+        }
+
+        let Some(map_id) = map_id else {
+            return Err(CommandError::user("missing map id or link").into());
+        };
+
+        Ok((map_id, params))
+    }
+
+    /// Short description of the set knobs like `+DT 99.10% 1200x 5x100 2m`.
+    pub fn describe(&self) -> String {
+        let mut parts = vec![format!("+{}", self.mods)];
+
+        if let Some(accuracy) = self.accuracy {
+            parts.push(format!("{accuracy:.2}%"));
+        }
+
+        if let Some(combo) = self.combo {
+            parts.push(format!("{combo}x"));
+        }
+
+        for (count, result) in [(self.n300, 300), (self.n100, 100), (self.n50, 50)] {
+            if let Some(count) = count {
+                parts.push(format!("{count}x{result}"));
+            }
+        }
+
+        if let Some(misses) = self.misses {
+            parts.push(format!("{misses}m"));
+        }
+
+        if let Some(rate) = self.clock_rate {
+            parts.push(format!("at {rate}x speed"));
+        }
+
+        if let Some(passed) = self.passed_objects {
+            parts.push(format!("{passed} objects played"));
+        }
+
+        parts.join(" ")
+    }
+}
+
+fn parse_in(input: &str, range: RangeInclusive<f64>) -> Option<f64> {
+    input.parse().ok().filter(|value| range.contains(value))
+}
+
+/// pp split up into skills like `aim 150.12 | speed 120.30 | acc 60.20 | fl 0.00`.
+pub fn format_breakdown(attrs: &OsuPerformanceAttributes) -> String {
+    format!(
+        "aim {aim:.2} | speed {speed:.2} | acc {acc:.2} | fl {fl:.2}",
+        aim = attrs.pp_aim,
+        speed = attrs.pp_speed,
+        acc = attrs.pp_acc,
+        fl = attrs.pp_flashlight,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tokenizer::tokenize;
+
+    use super::*;
+
+    fn parse(input: &str) -> Result<(u32, ScoreParams)> {
+        ScoreParams::parse_simulation(&tokenize(input))
+    }
+
+    #[test]
+    fn parses_all_knobs() {
+        let (map_id, params) =
+            parse("456 +dt 99.1% 1200x 2m 5x100 3X50 1000x300 rate=1.2 passed=500").unwrap();
+
+        assert_eq!(map_id, 456);
+        assert_eq!(
+            params,
+            ScoreParams {
+                mods: GameMods::DoubleTime,
+                accuracy: Some(99.1),
+                combo: Some(1200),
+                n300: Some(1000),
+                n100: Some(5),
+                n50: Some(3),
+                misses: Some(2),
+                passed_objects: Some(500),
+                clock_rate: Some(1.2),
+            }
+        );
+        assert_eq!(
+            params.describe(),
+            "+DT 99.10% 1200x 1000x300 5x100 3x50 2m at 1.2x speed 500 objects played"
+        );
+    }
+
+    #[test]
+    fn parses_map_links_anywhere() {
+        let (map_id, params) = parse("+HD 3miss https://osu.ppy.sh/beatmapsets/1#osu/456").unwrap();
+
+        assert_eq!(map_id, 456);
+        assert_eq!(params.mods, GameMods::Hidden);
+        assert_eq!(params.misses, Some(3));
+        assert_eq!(params.describe(), "+HD 3m");
+    }
+
+    #[test]
+    fn rejects_invalid_knobs() {
+        for input in [
+            "456 101%",
+            "456 rate=3",
+            "456 +XY",
+            "456 5x200",
+            "456 peppy",
+            "99%",
+        ] {
+            assert!(parse(input).is_err(), "{input}");
+        }
+    }
+}
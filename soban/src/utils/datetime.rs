@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use time::OffsetDateTime;
 
 // https://github.com/MaxOhn/Bathbot/blob/main/bathbot-util/src/datetime.rs#L28-L89
@@ -49,3 +51,77 @@ impl RelativeTime for OffsetDateTime {
         )
     }
 }
+
+/// Format a duration with its two most significant units, e.g. `3d 4h` or `5m 12s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    let units = [
+        (secs / 86_400, "d"),
+        (secs / 3600 % 24, "h"),
+        (secs / 60 % 60, "m"),
+        (secs % 60, "s"),
+    ];
+
+    let first = units
+        .iter()
+        .position(|(amount, _)| *amount > 0)
+        .unwrap_or(units.len() - 1);
+
+    units[first..]
+        .iter()
+        .take(2)
+        .map(|(amount, unit)| format!("{amount}{unit}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration as TimeDuration;
+
+    use super::*;
+
+    fn ago(duration: TimeDuration) -> String {
+        (OffsetDateTime::now_utc() - duration).to_relative()
+    }
+
+    #[test]
+    fn relative_units() {
+        // the clock may tick between creating the timestamp and comparing it
+        let secs = ago(TimeDuration::seconds(30));
+        assert!(
+            secs == "30 seconds ago" || secs == "31 seconds ago",
+            "{secs}"
+        );
+
+        assert_eq!(ago(TimeDuration::minutes(5)), "5 minutes ago");
+        assert_eq!(ago(TimeDuration::hours(1)), "1 hour ago");
+        assert_eq!(ago(TimeDuration::days(3)), "3 days ago");
+        assert_eq!(ago(TimeDuration::weeks(2)), "2 weeks ago");
+    }
+
+    #[test]
+    fn relative_months_and_years() {
+        // amount of months depends on the current day of the month
+        let months = ago(TimeDuration::days(95));
+        assert!(
+            months == "3 months ago" || months == "4 months ago",
+            "{months}"
+        );
+
+        assert_eq!(ago(TimeDuration::days(2 * 365 + 10)), "2 years ago");
+    }
+
+    #[test]
+    fn duration_two_units() {
+        assert_eq!(format_duration(Duration::from_secs(0)), "0s");
+        assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+        assert_eq!(format_duration(Duration::from_secs(61)), "1m 1s");
+        assert_eq!(format_duration(Duration::from_secs(3 * 3600 + 5)), "3h 0m");
+        assert_eq!(
+            format_duration(Duration::from_secs(2 * 86_400 + 7200)),
+            "2d 2h"
+        );
+    }
+}
@@ -1,3 +1,9 @@
 pub mod beatmap;
+pub mod calc;
 pub mod datetime;
 pub mod osu;
+pub mod pp;
+pub mod random;
+pub mod rank;
+pub mod recent;
+pub mod suggest;
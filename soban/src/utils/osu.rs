@@ -1,95 +1,561 @@
-use std::sync::Arc;
+use std::{future::Future, sync::Arc};
 
 use eyre::{Report, Result};
 
-use rosu_pp::{BeatmapExt, DifficultyAttributes, PerformanceAttributes};
+use futures::{stream, StreamExt, TryStreamExt};
+use rosu_pp::{
+    osu::OsuPerformanceAttributes, Beatmap, BeatmapExt, DifficultyAttributes, PerformanceAttributes,
+};
 use rosu_v2::{
-    prelude::{GameMods, OsuError, Score},
+    prelude::{GameMode, Grade, OsuError, Score, User, UserStatistics},
     request::UserId,
 };
 
-use crate::{utils::datetime::RelativeTime, CommandOrigin, Context};
+use crate::{
+    tokenizer::{tokenize, Token},
+    utils::{
+        datetime::RelativeTime,
+        pp,
+        rank::{self, Rank},
+        recent::{self, RecentFilter},
+    },
+    CommandError, Context, Embed, Reply,
+};
 
-use super::beatmap::get_beatmap;
+use super::{
+    beatmap::{get_beatmap, map_name, MapAttributes},
+    calc::{self, ScoreParams},
+};
+
+/// Amount of maps that are looked up or calculated at the same time
+const MAP_CONCURRENCY: usize = 8;
 
 struct CalculatedScore {
-    cs: f32,
-    ar: f64,
-    od: f64,
+    map: MapAttributes,
     stars: f64,
+    /// pp if the score was a full combo, or for fails if the map was passed
     fc_pp: f64,
+    /// The score as it was played, recalculated
+    actual: OsuPerformanceAttributes,
+    /// How far into the map failed scores got
+    progress: Option<Progress>,
+}
+
+struct Progress {
+    passed_objects: usize,
+    total_objects: usize,
+}
+
+impl Progress {
+    fn percent(&self) -> f64 {
+        100.0 * self.passed_objects as f64 / self.total_objects.max(1) as f64
+    }
+}
+
+/// How much of a score is shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScoreDetails {
+    /// A single line with the most important stats
+    #[default]
+    Summary,
+    /// The summary followed by the breakdown
+    Verbose,
+    /// Only how the pp and stars are made up
+    Breakdown,
 }
 
 pub struct RecentArgs {
-    pub user: UserId,
+    pub user: UserArg,
     pub idx: Option<u32>,
     pub include_fails: bool,
+    pub details: ScoreDetails,
 }
 
-pub async fn handle_osu(
-    ctx: Arc<Context>,
-    origin: CommandOrigin<'_>,
-    user_id: UserId,
-) -> Result<()> {
-    match format_user(ctx, user_id).await {
-        Ok(response) => origin.send(&response).await?,
-        Err(err) => {
-            if let Some(OsuError::NotFound) = err.downcast_ref::<OsuError>() {
-                origin.send("couldn't find user").await?;
-            } else {
-                origin.send("couldn't reach osu!api").await?;
-                return Err(err);
-            }
+pub async fn handle_osu(ctx: Arc<Context>, user: UserArg, fresh: bool) -> Result<Reply> {
+    let embed = format_user(ctx, &user, fresh).await?;
+
+    Ok(embed.into())
+}
+
+pub async fn handle_recent(ctx: Arc<Context>, args: RecentArgs) -> Result<Reply> {
+    let offset = args.idx.unwrap_or(1).saturating_sub(1) as usize;
+    let user = get_user(&ctx, &args.user, false).await?;
+
+    let scores = ctx
+        .osu
+        .recent_scores(user.user_id, args.include_fails, offset, 1)
+        .await?;
+
+    if scores.is_empty() {
+        return Err(CommandError::user("no recent scores found").into());
+    }
+
+    let score = &scores[0];
+    let calc = calculate_score(&ctx, score).await?;
+
+    let reply = match args.details {
+        ScoreDetails::Summary => Reply::text(format_score(score, &calc)),
+        ScoreDetails::Verbose => {
+            let embed = Embed::new(format_score(score, &calc));
+
+            format_breakdown(embed, score, &calc).into()
+        }
+        ScoreDetails::Breakdown => {
+            let title = format!("{} +{}", score_map_name(&ctx, score).await?, score.mods);
+
+            format_breakdown(Embed::new(title), score, &calc).into()
         }
+    };
+
+    Ok(reply)
+}
+
+pub async fn handle_nochoke(ctx: Arc<Context>, user: UserArg) -> Result<Reply> {
+    let user = get_user(&ctx, &user, false).await?;
+    let stats = user_stats(&user)?;
+    let scores = get_best(&ctx, user.user_id).await?;
+
+    let unchoked = join_buffered(scores.iter().map(|score| unchoked_pp(&ctx, score))).await?;
+
+    let actual: Vec<f64> = scores.iter().map(score_pp).collect();
+    let bonus = pp::bonus_pp(f64::from(stats.pp), &actual);
+
+    let mut sorted = unchoked.clone();
+    pp::sort_desc(&mut sorted);
+    let total = pp::weighted_pp(sorted) + bonus;
+
+    let gains = actual
+        .iter()
+        .zip(&unchoked)
+        .map(|(actual, unchoked)| unchoked - actual);
+    let count = gains.clone().filter(|gain| *gain > 0.0).count();
+
+    let title = format!(
+        "{username} - {current:.2}pp → {total:.2}pp ({diff:+.2}pp) if unchoked",
+        username = user.username,
+        current = stats.pp,
+        diff = total - f64::from(stats.pp),
+    );
+
+    let mut embed = Embed::new(title)
+        .url(format!("https://osu.ppy.sh/users/{}", user.user_id))
+        .field("Unchoked", format!("{count} of {} scores", scores.len()))
+        .field("Rank", rank::global_rank_change(&ctx, stats, total).await?);
+
+    let biggest = gains
+        .enumerate()
+        .filter(|(_, gain)| *gain > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((idx, _)) = biggest {
+        let score = &scores[idx];
+
+        let value = format!(
+            "#{pos} https://osu.ppy.sh/b/{id} +{mods} {actual:.2}pp → {unchoked:.2}pp",
+            pos = idx + 1,
+            id = score.map_id,
+            mods = score.mods,
+            actual = actual[idx],
+            unchoked = unchoked[idx],
+        );
+
+        embed = embed.field("Biggest unchoke", value);
     }
 
-    Ok(())
+    Ok(embed.into())
 }
 
-pub async fn handle_recent(
+pub async fn handle_recent_list(
     ctx: Arc<Context>,
-    origin: CommandOrigin<'_>,
-    args: RecentArgs,
-) -> Result<()> {
-    match get_recent(ctx, args).await {
-        Ok(response) => origin.send(&response).await?,
-        Err(err) => {
-            if let Some(OsuError::NotFound) = err.downcast_ref::<OsuError>() {
-                origin.send("couldn't find user").await?;
-            } else {
-                origin.send("couldn't reach osu!api").await?;
-                return Err(err);
+    user: UserArg,
+    filter: RecentFilter,
+    page: u32,
+) -> Result<Reply> {
+    let user = get_user(&ctx, &user, false).await?;
+    let scores = ctx
+        .osu
+        .recent_scores(user.user_id, !filter.passes_only, 0, recent::RECENT_LIMIT)
+        .await?;
+
+    let names = join_buffered(scores.iter().map(|score| score_map_name(&ctx, score))).await?;
+
+    let (scores, names): (Vec<Score>, Vec<String>) = scores
+        .into_iter()
+        .zip(names)
+        .filter(|(score, name)| filter.matches(score, name))
+        .unzip();
+
+    // stars need the map's file, so they're only calculated for every score
+    // if they're filtered by and otherwise just for the listed page
+    let (scores, names, stars) = if filter.min_stars.is_some() {
+        let stars = scores_stars(&ctx, scores.iter()).await?;
+        let mut kept = (Vec::new(), Vec::new(), Vec::new());
+
+        for ((score, name), stars) in scores.into_iter().zip(names).zip(stars) {
+            if filter.matches_stars(stars) {
+                kept.0.push(score);
+                kept.1.push(name);
+                kept.2.push(Some(stars));
             }
         }
+
+        kept
+    } else {
+        let stars = vec![None; scores.len()];
+
+        (scores, names, stars)
+    };
+
+    if scores.is_empty() {
+        return Err(CommandError::user("no recent scores found").into());
+    }
+
+    let groups = recent::group_tries(&scores);
+    let pages = groups.len().div_ceil(recent::PAGE_SIZE);
+    let page = page.max(1) as usize;
+
+    if page > pages {
+        let msg = match pages {
+            1 => "there is only 1 page of recent scores".to_owned(),
+            _ => format!("there are only {pages} pages of recent scores"),
+        };
+
+        return Err(CommandError::user(msg).into());
+    }
+
+    let mut embed = Embed::new(format!("Recent scores of {}", user.username))
+        .url(format!("https://osu.ppy.sh/users/{}", user.user_id))
+        .footer(format!("page {page}/{pages}"));
+
+    let listed: Vec<_> = groups
+        .iter()
+        .enumerate()
+        .skip((page - 1) * recent::PAGE_SIZE)
+        .take(recent::PAGE_SIZE)
+        .map(|(idx, tries)| (idx, tries, recent::best_try(&scores, tries)))
+        .collect();
+
+    let missing = listed
+        .iter()
+        .filter(|(.., best)| stars[*best].is_none())
+        .map(|(.., best)| &scores[*best]);
+
+    let mut calculated = scores_stars(&ctx, missing).await?.into_iter();
+
+    for (idx, tries, best) in listed {
+        let name = &names[best];
+        let score = &scores[best];
+
+        let stars = match stars[best] {
+            Some(stars) => stars,
+            None => calculated.next().expect("missing stars"),
+        };
+
+        let summary = format!(
+            "{grade} +{mods} {acc:.2}% {fc_or_misses} ★{stars:.2}{pp}",
+            grade = if score.passed { score.grade } else { Grade::F },
+            mods = score.mods,
+            acc = score.accuracy,
+            fc_or_misses = match score.perfect {
+                true => "FC".to_owned(),
+                false => format!("{}m", score.statistics.count_miss),
+            },
+            pp = score
+                .pp
+                .map_or_else(String::new, |pp| format!(" {pp:.2}pp")),
+        );
+
+        let value = match tries.len() {
+            1 => summary,
+            count => format!("{count} tries, best {summary}"),
+        };
+
+        // the first try is the latest
+        let date = scores[tries[0]].ended_at.to_relative();
+
+        embed = embed.field(format!("{}. {name}", idx + 1), format!("{value} - {date}"));
+    }
+
+    Ok(embed.into())
+}
+
+/// Stars of the scores' maps with their mods, in order.
+async fn scores_stars<'a>(
+    ctx: &Context,
+    scores: impl Iterator<Item = &'a Score>,
+) -> Result<Vec<f64>> {
+    join_buffered(scores.map(|score| async move {
+        let map = get_beatmap(ctx, score.map_id).await?;
+
+        Ok(map.stars().mods(score.mods.bits()).calculate().stars())
+    }))
+    .await
+}
+
+/// Run the futures [`MAP_CONCURRENCY`] at a time, collecting their outputs
+/// in order.
+///
+/// The futures are collected before they're streamed since mapping the
+/// stream itself trips up the compiler when proving that the future is `Send`.
+async fn join_buffered<T>(
+    futures: impl IntoIterator<Item = impl Future<Output = Result<T>>>,
+) -> Result<Vec<T>> {
+    let futures: Vec<_> = futures.into_iter().collect();
+
+    stream::iter(futures)
+        .buffered(MAP_CONCURRENCY)
+        .try_collect()
+        .await
+}
+
+/// Name of the score's map, looked up if the score doesn't come with it.
+async fn score_map_name(ctx: &Context, score: &Score) -> Result<String> {
+    let name = match (&score.mapset, &score.map) {
+        (Some(mapset), Some(map)) => {
+            format!("{} - {} [{}]", mapset.artist, mapset.title, map.version)
+        }
+        _ => map_name(&*ctx.cache.beatmap(&*ctx.osu, score.map_id, false).await?),
+    };
+
+    Ok(name)
+}
+
+/// The newest of the user's top plays, showing how much each adds to their
+/// total compared to the same top plays without it.
+pub async fn handle_recent_best(ctx: Arc<Context>, user: UserArg, amount: usize) -> Result<Reply> {
+    let user = get_user(&ctx, &user, false).await?;
+    let scores = get_best(&ctx, user.user_id).await?;
+
+    let top: Vec<f64> = scores.iter().map(score_pp).collect();
+    let total = pp::weighted_pp(top.iter().copied());
+
+    let mut newest: Vec<usize> = (0..scores.len()).collect();
+    newest.sort_by_key(|&idx| std::cmp::Reverse(scores[idx].ended_at));
+
+    let mut embed = Embed::new(format!("Newest top plays of {}", user.username))
+        .url(format!("https://osu.ppy.sh/users/{}", user.user_id));
+
+    for idx in newest.into_iter().take(amount) {
+        let score = &scores[idx];
+
+        let without = top
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != idx)
+            .map(|(_, pp)| *pp);
+        let gained = total - pp::weighted_pp(without);
+
+        let value = format!(
+            "+{mods} {acc:.2}% {pp:.2}pp, {weighted:.2}pp weighted, +{gained:.2}pp total - {date}",
+            mods = score.mods,
+            acc = score.accuracy,
+            pp = top[idx],
+            weighted = top[idx] * pp::weight(idx),
+            date = score.ended_at.to_relative(),
+        );
+
+        let name = score_map_name(&ctx, score).await?;
+        embed = embed.field(format!("#{} {name}", idx + 1), value);
     }
 
-    Ok(())
+    Ok(embed.into())
 }
 
-async fn get_recent(ctx: Arc<Context>, args: RecentArgs) -> Result<String> {
-    let offset = args.idx.unwrap_or(1).saturating_sub(1) as usize;
+pub async fn handle_whatif(ctx: Arc<Context>, user: UserArg, pp: f64) -> Result<Reply> {
+    let user = get_user(&ctx, &user, false).await?;
+    let stats = user_stats(&user)?;
+    let scores = ctx.osu.best_scores(user.user_id, pp::TOP_PLAYS).await?;
 
-    let scores = ctx
-        .osu
-        .user_scores(args.user)
-        .recent()
-        .include_fails(args.include_fails)
-        .offset(offset)
-        .limit(1)
-        .await?;
+    let mut top: Vec<f64> = scores.iter().map(score_pp).collect();
+    let current = f64::from(stats.pp);
+    let bonus = pp::bonus_pp(current, &top);
+
+    let title = format!("What if {} got a {pp:.2}pp play?", user.username);
+    let embed = Embed::new(title).url(format!("https://osu.ppy.sh/users/{}", user.user_id));
+
+    let Some(idx) = pp::insert_play(&mut top, pp) else {
+        let value = format!("not in their top {}, no pp gained", pp::TOP_PLAYS);
+
+        return Ok(embed.field("Top play", value).into());
+    };
+
+    let total = pp::weighted_pp(top) + bonus;
+
+    let embed = embed
+        .field("Top play", format!("#{}", idx + 1))
+        .field(
+            "Total pp",
+            format!(
+                "{current:.2}pp → {total:.2}pp ({diff:+.2}pp)",
+                diff = total - current
+            ),
+        )
+        .field("Rank", rank::global_rank_change(&ctx, stats, total).await?);
+
+    Ok(embed.into())
+}
+
+/// What the user needs to reach a rank or total pp.
+pub enum RankTarget {
+    Rank(Rank),
+    Pp(f64),
+}
+
+pub async fn handle_rank(ctx: Arc<Context>, user: UserArg, target: RankTarget) -> Result<Reply> {
+    let user = get_user(&ctx, &user, false).await?;
+    let stats = user_stats(&user)?;
+    let current = f64::from(stats.pp);
+
+    let (target_name, target_pp) = match target {
+        RankTarget::Rank(rank) => {
+            let pp = rank::pp_at_rank(&ctx, &rank).await?;
+
+            (format!("{rank} ({pp:.2}pp)"), pp)
+        }
+        RankTarget::Pp(pp) => (format!("{pp:.2}pp"), pp),
+    };
+
+    if current >= target_pp {
+        let response = format!(
+            "{username} already has {current:.2}pp, enough for {target_name}",
+            username = user.username,
+        );
+
+        return Ok(Reply::text(response));
+    }
+
+    let scores = ctx.osu.best_scores(user.user_id, pp::TOP_PLAYS).await?;
+    let top: Vec<f64> = scores.iter().map(score_pp).collect();
+    let bonus = pp::bonus_pp(current, &top);
+
+    let title = format!("What {} needs for {target_name}", user.username);
+
+    let mut embed = Embed::new(title)
+        .url(format!("https://osu.ppy.sh/users/{}", user.user_id))
+        .field(
+            "Missing pp",
+            format!(
+                "{current:.2}pp → {target_pp:.2}pp (+{diff:.2}pp)",
+                diff = target_pp - current
+            ),
+        );
+
+    if let Some((pp, idx)) = pp::required_play(&top, bonus, target_pp) {
+        let value = format!("a {pp:.2}pp play, #{} in their top plays", idx + 1);
+        embed = embed.field("Single play", value);
+    }
+
+    Ok(embed.into())
+}
+
+/// Get the user's top plays, telling the user if they don't have any.
+async fn get_best(ctx: &Context, user_id: u32) -> Result<Vec<Score>> {
+    let scores = ctx.osu.best_scores(user_id, pp::TOP_PLAYS).await?;
 
     if scores.is_empty() {
-        return Ok("no recent scores found".to_owned());
+        return Err(CommandError::user("no top scores found").into());
     }
 
-    let score = &scores[0];
-    let response = format_score(score).await?;
+    Ok(scores)
+}
+
+fn score_pp(score: &Score) -> f64 {
+    score.pp.map_or(0.0, f64::from)
+}
+
+/// pp of the score if it had been a full combo, or its actual pp if that's
+/// higher or it's not an osu!standard score.
+async fn unchoked_pp(ctx: &Context, score: &Score) -> Result<f64> {
+    let pp = score_pp(score);
+
+    if score.perfect || score.mode != GameMode::Osu {
+        return Ok(pp);
+    }
+
+    let map = get_beatmap(ctx, score.map_id).await?;
+    let attr = map.stars().mods(score.mods.bits()).calculate();
 
-    Ok(response)
+    Ok(full_combo(&map, score, attr).pp().max(pp))
 }
 
-async fn format_user(ctx: Arc<Context>, user_id: UserId) -> Result<String> {
-    let osu_user = ctx.osu.user(user_id).await?;
+/// Calculate the score as if it had been a full combo, counting misses as 300s.
+fn full_combo(map: &Beatmap, score: &Score, attr: DifficultyAttributes) -> PerformanceAttributes {
+    let params = ScoreParams {
+        mods: score.mods,
+        n100: Some(score.statistics.count_100 as usize),
+        n50: Some(score.statistics.count_50 as usize),
+        ..Default::default()
+    };
+
+    params.performance(map).attributes(attr).calculate()
+}
+
+/// The failed score with the rest of the map played without further mistakes.
+fn if_passed(map: &Beatmap, score: &Score, attr: DifficultyAttributes) -> PerformanceAttributes {
+    let params = ScoreParams {
+        mods: score.mods,
+        n100: Some(score.statistics.count_100 as usize),
+        n50: Some(score.statistics.count_50 as usize),
+        misses: Some(score.statistics.count_miss as usize),
+        ..Default::default()
+    };
+
+    params.performance(map).attributes(attr).calculate()
+}
+
+pub async fn handle_simulate(ctx: Arc<Context>, map_id: u32, params: ScoreParams) -> Result<Reply> {
+    let map_info = ctx.cache.beatmap(&*ctx.osu, map_id, false).await?;
+    let map = get_beatmap(&ctx, map_id).await?;
+
+    let max_params = ScoreParams {
+        mods: params.mods,
+        clock_rate: params.clock_rate,
+        ..Default::default()
+    };
+
+    let (PerformanceAttributes::Osu(attrs), PerformanceAttributes::Osu(max)) = (
+        params.performance(&map).calculate(),
+        max_params.performance(&map).calculate(),
+    ) else {
+        return Err(CommandError::user("only osu!standard maps can be simulated").into());
+    };
+
+    let difficulty = &attrs.difficulty;
+    let map_attrs = MapAttributes::new(&map, &map_info, params.mods, params.clock_rate);
+
+    let title = format!("{} {}", map_name(&map_info), params.describe());
+
+    let embed = Embed::new(title)
+        .url(format!("https://osu.ppy.sh/b/{map_id}"))
+        .field(
+            "Map",
+            format!(
+                "{map_attrs} | {bpm:.0} BPM | {length}",
+                bpm = map_attrs.bpm,
+                length = map_attrs.length(),
+            ),
+        )
+        .field(
+            "Stars",
+            format!(
+                "★{stars:.2} (aim {aim:.2}, speed {speed:.2}) | max combo {combo}x",
+                stars = difficulty.stars,
+                aim = difficulty.aim,
+                speed = difficulty.speed,
+                combo = difficulty.max_combo,
+            ),
+        )
+        .field(
+            "pp",
+            format!("{:.2}pp ({})", attrs.pp, calc::format_breakdown(&attrs)),
+        )
+        .field("Max pp", format!("{:.2}pp", max.pp));
+
+    Ok(embed.into())
+}
+
+async fn format_user(ctx: Arc<Context>, user: &UserArg, fresh: bool) -> Result<Embed> {
+    let osu_user = get_user(&ctx, user, fresh).await?;
     let osu_user_stats = osu_user.statistics.as_ref().expect("missing user stats");
     let rank = osu_user_stats
         .global_rank
@@ -98,100 +564,510 @@ async fn format_user(ctx: Arc<Context>, user_id: UserId) -> Result<String> {
         .country_rank
         .map_or("-".to_owned(), |r| r.to_string());
 
-    let response = format!(
-        "{username} - {pp}pp (#{rank}) ({country_code}#{country_rank})\nRanked Score: {ranked_score}",
+    let title = format!(
+        "{username} - {pp}pp (#{rank}) ({country_code}#{country_rank})",
         username = osu_user.username,
         pp = osu_user_stats.pp,
         country_code = osu_user.country_code,
-        ranked_score = osu_user_stats.ranked_score,
     );
 
-    Ok(response)
-}
+    let embed = Embed::new(title)
+        .url(format!("https://osu.ppy.sh/users/{}", osu_user.user_id))
+        .field("Ranked Score", osu_user_stats.ranked_score);
 
-async fn format_score(score: &Score) -> Result<String> {
-    let calc = calculate_score(score).await?;
+    Ok(embed)
+}
 
+fn format_score(score: &Score, calc: &CalculatedScore) -> String {
     let map_stats = format!(
-        "CS{cs:.2} AR{ar:.2} OD{od:.2} ★{stars:.2}",
-        cs = calc.cs,
-        ar = calc.ar,
-        od = calc.od,
+        "{attrs} {bpm:.0}BPM {length} ★{stars:.2}",
+        attrs = calc.map,
+        bpm = calc.map.bpm,
+        length = calc.map.length(),
         stars = calc.stars
     );
 
-    let pp = match score.pp {
-        Some(pp) => format!(" {pp:.2}pp"),
-        None => "".to_owned(),
+    let pp = match (&calc.progress, score.pp) {
+        // the api doesn't give pp for fails
+        (Some(_), _) => format!(" {:.2}pp", calc.actual.pp),
+        (None, Some(pp)) => format!(" {pp:.2}pp"),
+        (None, None) => "".to_owned(),
+    };
+
+    let fc_or_misses = match score.perfect {
+        false => format!("{}m ", score.statistics.count_miss),
+        true => "FC ".to_owned(),
     };
 
-    let (fc_pp, fc_or_misses) = match score.perfect {
-        false => (
+    let (grade, progress, fc_pp) = match (&calc.progress, score.perfect) {
+        (Some(progress), _) => (
+            Grade::F,
+            format!("{:.2}% done ", progress.percent()),
+            format!(" >> {:.2}pp if passed", calc.fc_pp),
+        ),
+        (None, false) => (
+            score.grade,
+            "".to_owned(),
             format!(" >> {:.2}pp if FC", calc.fc_pp),
-            format!("{}m ", score.statistics.count_miss),
         ),
-        true => ("".to_owned(), "FC ".to_owned()),
+        (None, true) => (score.grade, "".to_owned(), "".to_owned()),
     };
 
-    Ok(format!(
-        "https://osu.ppy.sh/b/{id} {grade} +{mods} {acc:.2}% {fc_or_misses}{map_stats}{pp}{fc_pp} - {date}",
-        grade = score.grade,
+    format!(
+        "https://osu.ppy.sh/b/{id} {grade} +{mods} {acc:.2}% {fc_or_misses}{progress}{map_stats}{pp}{fc_pp} - {date}",
         id = score.map_id,
         mods = score.mods,
         acc = score.accuracy,
         date = score.ended_at.to_relative(),
-    ))
+    )
+}
+
+/// Add fields for how the score's pp and the map's stars are made up, with
+/// the pp the api reports next to the recalculated ones since they differ
+/// after pp reworks.
+///
+/// The api doesn't report which slider ends a score hit, so only the map's
+/// slider ends and how much of its aim they make up are shown.
+fn format_breakdown(embed: Embed, score: &Score, calc: &CalculatedScore) -> Embed {
+    let attrs = &calc.actual;
+    let difficulty = &attrs.difficulty;
+
+    let live = match (&calc.progress, score.pp) {
+        (Some(progress), _) => format!(
+            "{pp:.2}pp at {percent:.2}% of the map, {passed:.2}pp if passed",
+            pp = attrs.pp,
+            percent = progress.percent(),
+            passed = calc.fc_pp,
+        ),
+        (None, Some(pp)) => format!(
+            "{pp:.2}pp live, {recalc:.2}pp recalculated ({diff:+.2})",
+            recalc = attrs.pp,
+            diff = attrs.pp - f64::from(pp),
+        ),
+        (None, None) => format!("{:.2}pp recalculated", attrs.pp),
+    };
+
+    let stars = format!(
+        "★{stars:.2} (aim {aim:.2} | speed {speed:.2} | fl {fl:.2}) | combo {combo}/{max_combo}x",
+        stars = difficulty.stars,
+        aim = difficulty.aim,
+        speed = difficulty.speed,
+        fl = difficulty.flashlight,
+        combo = score.max_combo,
+        max_combo = difficulty.max_combo,
+    );
+
+    // the slider factor is the share of aim that's left without slider ends
+    let sliders = format!(
+        "{ends} slider ends, aim {no_ends:.2} without them (slider factor {factor:.2})",
+        ends = difficulty.n_sliders,
+        no_ends = difficulty.aim * difficulty.slider_factor,
+        factor = difficulty.slider_factor,
+    );
+
+    embed
+        .field("pp", format!("{live} | {}", calc::format_breakdown(attrs)))
+        .field("stars", stars)
+        .field("sliders", sliders)
 }
 
-pub fn parse_user_id(input: &str) -> Option<UserId> {
-    if input.is_empty() {
-        return None;
+/// A user as given in the arguments of a command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum UserArg {
+    Id(u32),
+    Name(String),
+    /// A bare number, which is looked up as name first since names can be
+    /// numeric too, and as id if there's no such user
+    NameOrId(String),
+}
+
+impl UserArg {
+    /// Parse all tokens as a single user, returning `None` if there are none.
+    ///
+    /// Besides plain names this understands `id:123`, `@name` and profile
+    /// links. Quoted names are taken as they are.
+    pub fn parse(tokens: &[Token]) -> Result<Option<Self>> {
+        let (first, rest) = match tokens {
+            [] => return Ok(None),
+            [token] if token.quoted => return Ok(Some(Self::Name(token.text.clone()))),
+            [first, rest @ ..] => (first.text.as_str(), rest),
+        };
+
+        if rest.is_empty() {
+            if let Some(id) = strip_prefix_ignore_case(first, "id:") {
+                return match id.parse() {
+                    Ok(id) => Ok(Some(Self::Id(id))),
+                    Err(_) => Err(CommandError::user(format!("invalid user id `{id}`")).into()),
+                };
+            }
+
+            if let Some(user) = parse_profile_url(first)? {
+                return Ok(Some(user));
+            }
+
+            if first.chars().all(|c| c.is_ascii_digit()) {
+                return Ok(Some(Self::NameOrId(first.to_owned())));
+            }
+        }
+
+        let first = first.strip_prefix('@').unwrap_or(first);
+
+        let name = [first]
+            .into_iter()
+            .chain(rest.iter().map(|token| token.text.as_str()))
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Ok((!name.is_empty()).then_some(Self::Name(name)))
     }
+}
 
-    let id = input
-        .split_whitespace()
-        .next()
-        .and_then(|arg| arg.parse::<u32>().ok())
-        .map_or_else(|| UserId::Name(input.into()), UserId::Id);
+/// Parse links like `https://osu.ppy.sh/users/2/osu` or `osu.ppy.sh/u/peppy`,
+/// returning `None` if the input isn't a link to the osu! website.
+fn parse_profile_url(input: &str) -> Result<Option<UserArg>> {
+    let without_scheme = ["https://", "http://"]
+        .into_iter()
+        .find_map(|scheme| strip_prefix_ignore_case(input, scheme))
+        .unwrap_or(input);
 
-    Some(id)
+    let Some(path) = ["osu.ppy.sh/", "old.ppy.sh/"]
+        .into_iter()
+        .find_map(|host| strip_prefix_ignore_case(without_scheme, host))
+    else {
+        return Ok(None);
+    };
+
+    let user = ["users/", "u/"]
+        .into_iter()
+        .find_map(|prefix| strip_prefix_ignore_case(path, prefix))
+        .and_then(|user| user.split(['/', '?', '#']).next())
+        .filter(|user| !user.is_empty());
+
+    let Some(user) = user else {
+        return Err(CommandError::user("that's not a link to a user profile").into());
+    };
+
+    let user = match user.parse() {
+        Ok(id) => UserArg::Id(id),
+        Err(_) => UserArg::Name(user.replace("%20", " ")),
+    };
+
+    Ok(Some(user))
 }
 
-pub async fn require_user_id(origin: CommandOrigin<'_>) -> Result<()> {
-    origin.send("missing username").await?;
+fn strip_prefix_ignore_case<'a>(input: &'a str, prefix: &str) -> Option<&'a str> {
+    let start = input.get(..prefix.len())?;
+
+    start
+        .eq_ignore_ascii_case(prefix)
+        .then(|| &input[prefix.len()..])
+}
 
-    Ok(())
+/// Arguments made up of a user and flags like `--fresh`, in any order.
+pub struct UserArgs {
+    pub user: Option<UserArg>,
+    flags: Vec<&'static str>,
 }
 
-async fn calculate_score(score: &Score) -> Result<CalculatedScore> {
-    let map = get_beatmap(score.map_id).await?;
+impl UserArgs {
+    /// Parse the input, taking every unquoted word that's one of `flags`,
+    /// ignoring case, out of the user.
+    ///
+    /// Words that look like flags but aren't in `flags` are rejected so that
+    /// typos don't end up being looked up as part of the name.
+    pub fn parse(input: &str, flags: &[&'static str]) -> Result<Self> {
+        let mut found = Vec::new();
+        let mut user = Vec::new();
+
+        for token in tokenize(input) {
+            if token.quoted {
+                user.push(token);
+                continue;
+            }
+
+            match flags
+                .iter()
+                .find(|flag| flag.eq_ignore_ascii_case(&token.text))
+            {
+                Some(flag) => found.push(*flag),
+                None if token.text.starts_with("--") => {
+                    let msg = format!("unknown option `{}`", token.text);
+
+                    return Err(CommandError::user(msg).into());
+                }
+                None => user.push(token),
+            }
+        }
+
+        Ok(Self {
+            user: UserArg::parse(&user)?,
+            flags: found,
+        })
+    }
+
+    pub fn has(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+}
+
+pub fn require_user_id() -> Result<Reply> {
+    Err(CommandError::user("missing username").into())
+}
+
+/// Get a user through the cache, telling the user if they don't exist.
+///
+/// Bare numbers are looked up by name first and by id if no user has that name.
+async fn get_user(ctx: &Context, user: &UserArg, fresh: bool) -> Result<Arc<User>> {
+    let result = match user {
+        UserArg::Id(id) => ctx.cache.user(&*ctx.osu, UserId::Id(*id), fresh).await,
+        UserArg::Name(name) => {
+            let user_id = UserId::Name(name.as_str().into());
+
+            ctx.cache.user(&*ctx.osu, user_id, fresh).await
+        }
+        UserArg::NameOrId(name) => {
+            let user_id = UserId::Name(name.as_str().into());
+
+            match ctx.cache.user(&*ctx.osu, user_id, fresh).await {
+                Err(OsuError::NotFound) => match name.parse() {
+                    Ok(id) => ctx.cache.user(&*ctx.osu, UserId::Id(id), fresh).await,
+                    Err(_) => Err(OsuError::NotFound),
+                },
+                result => result,
+            }
+        }
+    };
+
+    match result {
+        Ok(user) => Ok(user),
+        Err(OsuError::NotFound) => Err(CommandError::user("couldn't find user").into()),
+        Err(err) => Err(CommandError::from(err).into()),
+    }
+}
+
+/// Statistics of the user in osu!standard, which the api omits e.g. for
+/// restricted users.
+fn user_stats(user: &User) -> Result<&UserStatistics> {
+    user.statistics
+        .as_ref()
+        .ok_or_else(|| CommandError::user("user has no stats").into())
+}
+
+async fn calculate_score(ctx: &Context, score: &Score) -> Result<CalculatedScore> {
+    let map = get_beatmap(ctx, score.map_id).await?;
     let attr = map.stars().mods(score.mods.bits()).calculate();
-    let pp_res = map
-        .pp()
-        .attributes(attr)
-        .mods(score.mods.bits())
-        .n100(score.statistics.count_100 as usize)
-        .n50(score.statistics.count_50 as usize)
-        .calculate();
 
-    let cs = if score.mods.contains(GameMods::HardRock) {
-        map.cs * 1.3
-    } else if score.mods.contains(GameMods::Easy) {
-        map.cs * 0.5
+    let (actual, fc) = if score.passed {
+        let actual = ScoreParams::from_score(score)
+            .performance(&map)
+            .attributes(attr.clone())
+            .calculate();
+
+        (actual, full_combo(&map, score, attr))
     } else {
-        map.cs
+        // the difficulty of the played part differs from the whole map's
+        let actual = ScoreParams {
+            passed_objects: Some(score.total_hits() as usize),
+            ..ScoreParams::from_score(score)
+        }
+        .performance(&map)
+        .calculate();
+
+        (actual, if_passed(&map, score, attr))
+    };
+
+    // recent scores come with the map, other scores might not
+    let fetched;
+    let info = match score.map {
+        Some(ref info) => info,
+        None => {
+            fetched = ctx.cache.beatmap(&*ctx.osu, score.map_id, false).await?;
+            &*fetched
+        }
     };
 
-    match (pp_res.difficulty_attributes(), pp_res) {
-        (DifficultyAttributes::Osu(map_attr), PerformanceAttributes::Osu(pp)) => {
+    match (actual, fc) {
+        (PerformanceAttributes::Osu(actual), PerformanceAttributes::Osu(fc)) => {
+            let progress = (!score.passed).then(|| Progress {
+                passed_objects: score.total_hits() as usize,
+                total_objects: fc.difficulty.n_circles
+                    + fc.difficulty.n_sliders
+                    + fc.difficulty.n_spinners,
+            });
+
             Ok(CalculatedScore {
-                cs,
-                ar: map_attr.ar,
-                od: map_attr.od,
-                stars: map_attr.stars,
-                fc_pp: pp.pp,
+                map: MapAttributes::new(&map, info, score.mods, None),
+                stars: fc.difficulty.stars,
+                fc_pp: fc.pp,
+                actual,
+                progress,
             })
         }
-        _ => return Err(Report::msg("not an osu map")),
+        _ => Err(Report::msg("not an osu map")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fixture_json, minutes_ago, TestContext};
+
+    fn parse_user(input: &str) -> Option<UserArg> {
+        UserArg::parse(&tokenize(input)).unwrap()
+    }
+
+    fn name(name: &str) -> Option<UserArg> {
+        Some(UserArg::Name(name.into()))
+    }
+
+    #[test]
+    fn parse_user_variants() {
+        assert_eq!(parse_user(""), None);
+        assert_eq!(parse_user("\"\""), None);
+        assert_eq!(parse_user("peppy"), name("peppy"));
+        assert_eq!(parse_user("some user"), name("some user"));
+        assert_eq!(parse_user("some\u{3000} user"), name("some user"));
+        assert_eq!(parse_user("\"some user\""), name("some user"));
+        assert_eq!(parse_user("@peppy"), name("peppy"));
+        assert_eq!(parse_user("@some user"), name("some user"));
+    }
+
+    #[test]
+    fn parse_user_numbers() {
+        assert_eq!(parse_user("2"), Some(UserArg::NameOrId("2".into())));
+        assert_eq!(parse_user("ID:2"), Some(UserArg::Id(2)));
+        assert_eq!(parse_user("\"1234\""), name("1234"));
+        assert_eq!(parse_user("@1234"), name("1234"));
+        assert_eq!(parse_user("2 peppy"), name("2 peppy"));
+
+        assert!(UserArg::parse(&tokenize("id:peppy")).is_err());
+    }
+
+    #[test]
+    fn parse_user_profile_urls() {
+        let id = Some(UserArg::Id(2));
+
+        assert_eq!(parse_user("https://osu.ppy.sh/users/2"), id);
+        assert_eq!(parse_user("https://osu.ppy.sh/users/2/osu"), id);
+        assert_eq!(parse_user("http://osu.ppy.sh/u/2#top"), id);
+        assert_eq!(parse_user("osu.ppy.sh/users/2?mode=osu"), id);
+        assert_eq!(parse_user("https://old.ppy.sh/u/peppy"), name("peppy"));
+        assert_eq!(
+            parse_user("osu.ppy.sh/users/some%20user"),
+            name("some user")
+        );
+
+        assert!(UserArg::parse(&tokenize("https://osu.ppy.sh/beatmaps/1")).is_err());
+    }
+
+    #[test]
+    fn user_args_take_flags_anywhere() {
+        let parse = |input| {
+            let args = UserArgs::parse(input, &["--fresh"]).unwrap();
+            let fresh = args.has("--fresh");
+
+            (args.user, fresh)
+        };
+
+        assert_eq!(parse("peppy"), (name("peppy"), false));
+        assert_eq!(parse("--fresh peppy"), (name("peppy"), true));
+        assert_eq!(
+            parse("some --FRESH user --fresh"),
+            (name("some user"), true)
+        );
+        assert_eq!(parse("\"--fresh\""), (name("--fresh"), false));
+        assert_eq!(parse("--fresh"), (None, true));
+
+        assert!(UserArgs::parse("peppy --frsh", &["--fresh"]).is_err());
+    }
+
+    fn fixture_scores() -> Vec<Score> {
+        let mut scores = fixture_json("recent_scores.json");
+
+        for score in scores.as_array_mut().unwrap() {
+            score["ended_at"] = minutes_ago(5).into();
+        }
+
+        serde_json::from_str(&scores.to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn format_score_choke() {
+        let test = TestContext::new().await;
+        let score = &fixture_scores()[0];
+
+        let calc = calculate_score(&test.ctx, score).await.unwrap();
+        let formatted = format_score(score, &calc);
+
+        assert_eq!(
+            formatted,
+            "https://osu.ppy.sh/b/1 A +HD 97.36% 2m CS4 AR9 OD8 HP5 200BPM 0:10 ★5.62 \
+             80.50pp >> 179.32pp if FC - 5 minutes ago"
+        );
+    }
+
+    #[tokio::test]
+    async fn format_score_full_combo() {
+        let test = TestContext::new().await;
+        let score = &fixture_scores()[1];
+
+        let calc = calculate_score(&test.ctx, score).await.unwrap();
+        let formatted = format_score(score, &calc);
+
+        assert_eq!(
+            formatted,
+            "https://osu.ppy.sh/b/1 X +NM 100.00% FC CS4 AR9 OD8 HP5 200BPM 0:10 ★5.62 \
+             120.00pp - 5 minutes ago"
+        );
+    }
+
+    /// The first score, failed after 24 of the map's 48 objects
+    fn failed_score() -> Score {
+        let mut score = fixture_scores().remove(0);
+
+        score.passed = false;
+        score.grade = Grade::A;
+        score.pp = None;
+        score.statistics.count_300 = 20;
+        score.accuracy = 86.11;
+        score.max_combo = 20;
+
+        score
+    }
+
+    #[tokio::test]
+    async fn format_score_failed() {
+        let test = TestContext::new().await;
+        let score = &failed_score();
+
+        let calc = calculate_score(&test.ctx, score).await.unwrap();
+        let formatted = format_score(score, &calc);
+
+        assert_eq!(
+            formatted,
+            "https://osu.ppy.sh/b/1 F +HD 86.11% 2m 50.00% done CS4 AR9 OD8 HP5 200BPM 0:10 \
+             ★5.62 40.61pp >> 130.89pp if passed - 5 minutes ago"
+        );
+
+        assert_eq!(
+            format_breakdown(Embed::new("title"), score, &calc).fields,
+            [
+                (
+                    "pp".to_owned(),
+                    "40.61pp at 50.00% of the map, 130.89pp if passed | \
+                     aim 31.49 | speed 5.14 | acc 0.44 | fl 0.00"
+                        .to_owned()
+                ),
+                (
+                    "stars".to_owned(),
+                    "★4.55 (aim 2.53 | speed 1.58 | fl 0.67) | combo 20/27x".to_owned()
+                ),
+                (
+                    "sliders".to_owned(),
+                    "3 slider ends, aim 2.50 without them (slider factor 0.99)".to_owned()
+                ),
+            ]
+        );
     }
 }
@@ -0,0 +1,177 @@
+//! How a user's total pp is made up of their top plays.
+//!
+//! The total is the sum of the top plays' pp, each weighted by `0.95^i` for
+//! its zero-based position `i`, plus bonus pp for the amount of ranked plays.
+
+/// Amount of top plays that count towards a user's total pp
+pub const TOP_PLAYS: usize = 100;
+
+/// Factor the pp of each following top play are weighted with
+const WEIGHT_DECAY: f64 = 0.95;
+
+/// Weight of the top play at the zero-based position.
+pub fn weight(idx: usize) -> f64 {
+    WEIGHT_DECAY.powi(idx as i32)
+}
+
+/// Weighted sum of the pp, which must be sorted in descending order.
+pub fn weighted_pp(pp: impl IntoIterator<Item = f64>) -> f64 {
+    pp.into_iter()
+        .enumerate()
+        .map(|(idx, pp)| pp * weight(idx))
+        .sum()
+}
+
+/// Part of the user's total pp that doesn't come from their top plays.
+///
+/// This includes the bonus pp as well as rounding differences since the
+/// api only reports the total.
+pub fn bonus_pp(total: f64, top: &[f64]) -> f64 {
+    (total - weighted_pp(top.iter().copied())).max(0.0)
+}
+
+/// Sort pp in descending order like top plays are.
+pub fn sort_desc(pp: &mut [f64]) {
+    pp.sort_unstable_by(|a, b| b.total_cmp(a));
+}
+
+/// Insert a new play into top plays sorted in descending order, returning its
+/// zero-based position if it's within the top plays that count.
+pub fn insert_play(top: &mut Vec<f64>, pp: f64) -> Option<usize> {
+    let idx = top.partition_point(|&top_pp| top_pp >= pp);
+
+    if idx >= TOP_PLAYS {
+        return None;
+    }
+
+    top.insert(idx, pp);
+    top.truncate(TOP_PLAYS);
+
+    Some(idx)
+}
+
+/// pp of the single new play that brings a user with the top plays, sorted in
+/// descending order, and bonus pp to the target total, along with its
+/// zero-based position in the top plays.
+///
+/// Returns `None` if the total is already reached.
+pub fn required_play(top: &[f64], bonus: f64, target: f64) -> Option<(f64, usize)> {
+    let total_with = |pp: f64| {
+        let mut top = top.to_vec();
+        let idx = insert_play(&mut top, pp);
+
+        (weighted_pp(top) + bonus, idx)
+    };
+
+    if weighted_pp(top.iter().copied()) + bonus >= target {
+        return None;
+    }
+
+    // a play worth the whole target is always enough
+    let (mut lo, mut hi) = (0.0, target);
+
+    for _ in 0..64 {
+        let mid = (lo + hi) / 2.0;
+
+        if total_with(mid).0 >= target {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    let idx = total_with(hi).1.unwrap_or(TOP_PLAYS - 1);
+
+    Some((hi, idx))
+}
+
+/// Parse an amount of pp like `300`, `300.5` or `300pp`.
+pub fn parse_pp(input: &str) -> Option<f64> {
+    let len = input.len();
+
+    let number = match input.get(len.saturating_sub(2)..) {
+        Some(suffix) if suffix.eq_ignore_ascii_case("pp") => &input[..len - 2],
+        _ => input,
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|pp| pp.is_finite() && *pp >= 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weights_decay() {
+        assert_eq!(weight(0), 1.0);
+        assert!((weight(1) - 0.95).abs() < 1e-9);
+        assert!((weighted_pp([100.0, 100.0]) - 195.0).abs() < 1e-9);
+        assert_eq!(weighted_pp([]), 0.0);
+    }
+
+    #[test]
+    fn bonus_is_never_negative() {
+        assert!((bonus_pp(300.0, &[100.0, 100.0]) - 105.0).abs() < 1e-9);
+        assert_eq!(bonus_pp(100.0, &[200.0]), 0.0);
+    }
+
+    #[test]
+    fn inserts_into_top_plays() {
+        let mut top = vec![300.0, 200.0, 100.0];
+
+        assert_eq!(insert_play(&mut top, 250.0), Some(1));
+        assert_eq!(top, [300.0, 250.0, 200.0, 100.0]);
+
+        // ties go below existing plays
+        assert_eq!(insert_play(&mut top, 300.0), Some(1));
+
+        let mut full = vec![100.0; TOP_PLAYS];
+        assert_eq!(insert_play(&mut full, 50.0), None);
+        assert_eq!(insert_play(&mut full, 150.0), Some(0));
+        assert_eq!(full.len(), TOP_PLAYS);
+        assert_eq!(full[0], 150.0);
+    }
+
+    #[test]
+    fn finds_required_play() {
+        let top = [300.0, 200.0, 100.0];
+        let current = weighted_pp(top) + 50.0;
+
+        assert_eq!(required_play(&top, 50.0, current), None);
+
+        // replacing nothing, a 400pp play adds 400 and decays the others
+        let target = weighted_pp([400.0, 300.0, 200.0, 100.0]) + 50.0;
+        let (pp, idx) = required_play(&top, 50.0, target).unwrap();
+
+        assert!((pp - 400.0).abs() < 1e-6, "{pp}");
+        assert_eq!(idx, 0);
+
+        let target = weighted_pp([300.0, 200.0, 150.0, 100.0]) + 50.0;
+        let (pp, idx) = required_play(&top, 50.0, target).unwrap();
+
+        assert!((pp - 150.0).abs() < 1e-6, "{pp}");
+        assert_eq!(idx, 2);
+    }
+
+    #[test]
+    fn parses_pp() {
+        assert_eq!(parse_pp("300"), Some(300.0));
+        assert_eq!(parse_pp("300.5pp"), Some(300.5));
+        assert_eq!(parse_pp("300PP"), Some(300.0));
+        assert_eq!(parse_pp("pp"), None);
+        assert_eq!(parse_pp("-5"), None);
+        assert_eq!(parse_pp("inf"), None);
+        assert_eq!(parse_pp("péppy"), None);
+    }
+
+    #[test]
+    fn sorts_descending() {
+        let mut pp = [1.0, 3.0, 2.0];
+        sort_desc(&mut pp);
+
+        assert_eq!(pp, [3.0, 2.0, 1.0]);
+    }
+}
@@ -0,0 +1,6 @@
+use std::{collections::hash_map::RandomState, hash::BuildHasher, time::Instant};
+
+/// Random number that's good enough for jitter and identifiers, not for anything secret.
+pub fn random_u64() -> u64 {
+    RandomState::new().hash_one(Instant::now())
+}
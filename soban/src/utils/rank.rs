@@ -0,0 +1,294 @@
+//! Looking up where an amount of pp lands on the pp leaderboard.
+
+use std::fmt;
+
+use eyre::Result;
+use rosu_v2::prelude::UserStatistics;
+
+use crate::{CommandError, Context};
+
+/// Users per page of the pp leaderboard
+pub const PAGE_SIZE: u32 = 50;
+
+/// The api only serves the first 200 pages, i.e. the top 10,000
+const MAX_PAGE: u32 = 200;
+
+/// Where an amount of pp lands on the leaderboard.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RankEstimate {
+    /// Rank on the part of the leaderboard the api serves
+    Ranked(u32),
+    /// Rank below the served part, interpolated towards a known rank
+    Interpolated(u32),
+    /// Somewhere below the served part, which ends at the given rank
+    Below(u32),
+}
+
+impl fmt::Display for RankEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ranked(rank) => write!(f, "#{rank}"),
+            Self::Interpolated(rank) => write!(f, "~#{rank}"),
+            Self::Below(rank) => write!(f, ">#{rank}"),
+        }
+    }
+}
+
+/// Estimate the global or country rank a user with the total pp would have.
+///
+/// The leaderboard pages are searched for the first user with less pp. Below
+/// the last page, the rank is interpolated linearly between that page and
+/// `anchor`, a known rank and its pp like the user's current ones.
+pub async fn estimate_rank(
+    ctx: &Context,
+    country: Option<&str>,
+    pp: f64,
+    anchor: Option<(u32, f64)>,
+) -> Result<RankEstimate> {
+    let first = ctx.cache.rankings(&*ctx.osu, country, 1).await?;
+    let pages = first.total.div_ceil(PAGE_SIZE).clamp(1, MAX_PAGE);
+
+    let page_pp = |page: u32| async move {
+        let rankings = ctx.cache.rankings(&*ctx.osu, country, page).await?;
+        let pp: Vec<f64> = rankings
+            .ranking
+            .iter()
+            .filter_map(|user| user.statistics.as_ref())
+            .map(|stats| f64::from(stats.pp))
+            .collect();
+
+        Ok::<_, eyre::Report>(pp)
+    };
+
+    let last_page = page_pp(pages).await?;
+    let last_rank = (pages - 1) * PAGE_SIZE + last_page.len() as u32;
+
+    match last_page.last() {
+        Some(&last_pp) if last_pp >= pp => return Ok(below(last_rank, last_pp, pp, anchor)),
+        None => return Ok(RankEstimate::Ranked(1)),
+        Some(_) => {}
+    }
+
+    // first page whose last user has less pp
+    let (mut lo, mut hi) = (1, pages);
+
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+
+        match page_pp(mid).await?.last() {
+            Some(&last_pp) if last_pp >= pp => lo = mid + 1,
+            _ => hi = mid,
+        }
+    }
+
+    let above = page_pp(lo)
+        .await?
+        .iter()
+        .filter(|&&user_pp| user_pp >= pp)
+        .count();
+
+    Ok(RankEstimate::Ranked(
+        (lo - 1) * PAGE_SIZE + above as u32 + 1,
+    ))
+}
+
+fn below(last_rank: u32, last_pp: f64, pp: f64, anchor: Option<(u32, f64)>) -> RankEstimate {
+    match anchor {
+        Some((rank, anchor_pp)) if rank > last_rank && anchor_pp <= pp && anchor_pp < last_pp => {
+            let progress = (last_pp - pp) / (last_pp - anchor_pp);
+            let offset = (f64::from(rank - last_rank) * progress).round() as u32;
+
+            RankEstimate::Interpolated((last_rank + offset).max(last_rank + 1))
+        }
+        _ => RankEstimate::Below(last_rank),
+    }
+}
+
+/// A rank on the global or a country's leaderboard like `#1000` or `AU#42`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rank {
+    /// Uppercase country code for country ranks
+    pub country: Option<String>,
+    pub rank: u32,
+}
+
+impl Rank {
+    pub fn parse(input: &str) -> Option<Self> {
+        let (country, rank) = input.split_once('#')?;
+
+        let country = match country.len() {
+            0 => None,
+            2 if country.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(country.to_ascii_uppercase())
+            }
+            _ => return None,
+        };
+
+        let rank = rank.parse().ok().filter(|&rank| rank > 0)?;
+
+        Some(Self { country, rank })
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}#{}",
+            self.country.as_deref().unwrap_or_default(),
+            self.rank
+        )
+    }
+}
+
+/// pp of the user at the rank, telling the user if the rank can't be looked up.
+pub async fn pp_at_rank(ctx: &Context, rank: &Rank) -> Result<f64> {
+    let page = (rank.rank - 1) / PAGE_SIZE + 1;
+
+    if page > MAX_PAGE {
+        let msg = format!("only the top {} can be looked up", MAX_PAGE * PAGE_SIZE);
+
+        return Err(CommandError::user(msg).into());
+    }
+
+    let rankings = ctx
+        .cache
+        .rankings(&*ctx.osu, rank.country.as_deref(), page)
+        .await?;
+
+    rankings
+        .ranking
+        .get(((rank.rank - 1) % PAGE_SIZE) as usize)
+        .and_then(|user| user.statistics.as_ref())
+        .map(|stats| f64::from(stats.pp))
+        .ok_or_else(|| CommandError::user(format!("there's nobody at {rank}")).into())
+}
+
+/// The user's current global rank and the one they'd have with the new total,
+/// like `#1234 → ~#1100`.
+pub async fn global_rank_change(
+    ctx: &Context,
+    stats: &UserStatistics,
+    new_pp: f64,
+) -> Result<String> {
+    let anchor = stats.global_rank.map(|rank| (rank, f64::from(stats.pp)));
+    let estimate = estimate_rank(ctx, None, new_pp, anchor).await?;
+
+    let current = stats
+        .global_rank
+        .map_or_else(|| "-".to_owned(), |rank| format!("#{rank}"));
+
+    Ok(format!("{current} → {estimate}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_support::{ranking_page, TestContext};
+
+    use super::*;
+
+    /// Leaderboard of `pages` full pages where every user has 10pp less than
+    /// the one above, starting at 100,000pp.
+    async fn leaderboard(pages: u32) -> TestContext {
+        let test = TestContext::new().await;
+        let total = pages * PAGE_SIZE;
+
+        for page in 1..=pages {
+            let start = (page - 1) * PAGE_SIZE;
+            let pp: Vec<f64> = (start..start + PAGE_SIZE)
+                .map(|idx| 100_000.0 - 10.0 * f64::from(idx))
+                .collect();
+
+            test.stand_in.api(
+                &format!("rankings/osu/performance?cursor[page]={page}"),
+                &ranking_page(&pp, total),
+            );
+        }
+
+        test
+    }
+
+    #[tokio::test]
+    async fn ranks_within_the_leaderboard() {
+        let test = leaderboard(10).await;
+        let estimate = |pp| estimate_rank(&test.ctx, None, pp, None);
+
+        assert_eq!(estimate(200_000.0).await.unwrap(), RankEstimate::Ranked(1));
+        assert_eq!(estimate(100_000.0).await.unwrap(), RankEstimate::Ranked(2));
+        assert_eq!(estimate(99_995.0).await.unwrap(), RankEstimate::Ranked(2));
+
+        // user #321 has 96,800pp
+        assert_eq!(estimate(96_805.0).await.unwrap(), RankEstimate::Ranked(321));
+        assert_eq!(estimate(96_800.0).await.unwrap(), RankEstimate::Ranked(322));
+
+        // the last user has 95,010pp
+        assert_eq!(estimate(95_015.0).await.unwrap(), RankEstimate::Ranked(500));
+    }
+
+    #[tokio::test]
+    async fn interpolates_below_the_leaderboard() {
+        let test = leaderboard(2).await;
+        let estimate = |pp, anchor| estimate_rank(&test.ctx, None, pp, anchor);
+
+        // the last user, #100, has 99,010pp
+        assert_eq!(
+            estimate(99_000.0, None).await.unwrap(),
+            RankEstimate::Below(100)
+        );
+        assert_eq!(
+            estimate(98_010.0, Some((200, 97_010.0))).await.unwrap(),
+            RankEstimate::Interpolated(150)
+        );
+
+        // the anchor has more pp than the estimated amount
+        assert_eq!(
+            estimate(97_000.0, Some((200, 97_010.0))).await.unwrap(),
+            RankEstimate::Below(100)
+        );
+    }
+
+    #[test]
+    fn parses_ranks() {
+        let rank = |country: Option<&str>, rank| {
+            Some(Rank {
+                country: country.map(str::to_owned),
+                rank,
+            })
+        };
+
+        assert_eq!(Rank::parse("#1000"), rank(None, 1000));
+        assert_eq!(Rank::parse("au#42"), rank(Some("AU"), 42));
+        assert_eq!(Rank::parse("#0"), None);
+        assert_eq!(Rank::parse("1000"), None);
+        assert_eq!(Rank::parse("AUS#42"), None);
+        assert_eq!(Rank::parse("#-5"), None);
+        assert_eq!(Rank::parse("AU#42").unwrap().to_string(), "AU#42");
+    }
+
+    #[tokio::test]
+    async fn looks_up_pp_at_ranks() {
+        let test = leaderboard(2).await;
+        let ctx = &test.ctx;
+        let pp = |rank| async move { pp_at_rank(ctx, &Rank::parse(rank).unwrap()).await };
+
+        assert_eq!(pp("#1").await.unwrap(), 100_000.0);
+        assert_eq!(pp("#51").await.unwrap(), 99_500.0);
+        assert!(pp("#101").await.is_err());
+        assert!(pp("#10001").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn pages_are_cached() {
+        let test = leaderboard(4).await;
+
+        estimate_rank(&test.ctx, None, 99_000.0, None)
+            .await
+            .unwrap();
+        let requests = test.stand_in.requests().len();
+
+        estimate_rank(&test.ctx, None, 98_000.0, None)
+            .await
+            .unwrap();
+        assert_eq!(test.stand_in.requests().len(), requests);
+    }
+}
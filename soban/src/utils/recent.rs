@@ -0,0 +1,210 @@
+//! Filtering a user's recent scores and grouping repeated tries of a map.
+
+use std::str::FromStr;
+
+use eyre::Result;
+use rosu_v2::prelude::{GameMods, Score};
+
+use crate::{tokenizer::Token, CommandError};
+
+/// Amount of recent scores that are listed at most
+pub const RECENT_LIMIT: usize = 50;
+
+/// Maps per page of the list
+pub const PAGE_SIZE: usize = 10;
+
+/// Which recent scores are listed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecentFilter {
+    pub passes_only: bool,
+    /// Mods a score needs at least, where no mods only match nomod scores
+    pub mods: Option<GameMods>,
+    /// Lowercase words that all have to be part of the map's name
+    pub search: Vec<String>,
+    pub min_stars: Option<f64>,
+}
+
+impl RecentFilter {
+    /// Split filters off the tokens, returning the remaining ones.
+    ///
+    /// Filters are `--passes` or `-p`, mods like `+HD`, minimum stars like
+    /// `stars=5.5` and words of the map's name like `map=freedom`.
+    pub fn parse(tokens: Vec<Token>) -> Result<(Self, Vec<Token>)> {
+        let mut filter = Self::default();
+        let mut rest = Vec::new();
+
+        for token in tokens {
+            if token.quoted {
+                rest.push(token);
+                continue;
+            }
+
+            let word = token.text.to_lowercase();
+
+            let parsed = if word == "--passes" || word == "-p" {
+                filter.passes_only = true;
+                Some(())
+            } else if let Some(mods) = word.strip_prefix('+') {
+                GameMods::from_str(mods)
+                    .map(|mods| filter.mods = Some(mods))
+                    .ok()
+            } else if let Some(stars) = word.strip_prefix("stars=") {
+                stars
+                    .parse()
+                    .ok()
+                    .filter(|stars: &f64| stars.is_finite())
+                    .map(|stars| filter.min_stars = Some(stars))
+            } else if let Some(search) = word.strip_prefix("map=") {
+                (!search.is_empty()).then(|| filter.search.push(search.to_owned()))
+            } else {
+                rest.push(token);
+                continue;
+            };
+
+            if parsed.is_none() {
+                let msg = format!("couldn't understand `{}`", token.text);
+
+                return Err(CommandError::user(msg).into());
+            }
+        }
+
+        Ok((filter, rest))
+    }
+
+    /// Whether the score on the map with the name is listed, apart from its
+    /// stars which are only calculated if there's a minimum.
+    pub fn matches(&self, score: &Score, map_name: &str) -> bool {
+        let mods = match self.mods {
+            Some(mods) if mods.is_empty() => score.mods.is_empty(),
+            Some(mods) => score.mods.contains(mods),
+            None => true,
+        };
+
+        let map_name = map_name.to_lowercase();
+
+        (score.passed || !self.passes_only)
+            && mods
+            && self.search.iter().all(|word| map_name.contains(word))
+    }
+
+    /// Whether the map's mod-adjusted stars are enough to be listed.
+    pub fn matches_stars(&self, stars: f64) -> bool {
+        self.min_stars.is_none_or(|min| stars >= min)
+    }
+}
+
+/// Group the indices of scores on the same map, ordered by the map's first
+/// appearance, i.e. its latest try for recent scores.
+pub fn group_tries(scores: &[Score]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+
+    for (idx, score) in scores.iter().enumerate() {
+        match groups
+            .iter_mut()
+            .find(|group| scores[group[0]].map_id == score.map_id)
+        {
+            Some(group) => group.push(idx),
+            None => groups.push(vec![idx]),
+        }
+    }
+
+    groups
+}
+
+/// Index of the best of the tries, passes before fails and then by score.
+pub fn best_try(scores: &[Score], tries: &[usize]) -> usize {
+    tries
+        .iter()
+        .copied()
+        .max_by_key(|&idx| (scores[idx].passed, scores[idx].score))
+        .expect("empty group of tries")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_support::{fixture_json, minutes_ago},
+        tokenizer::tokenize,
+    };
+
+    use super::*;
+
+    fn scores() -> Vec<Score> {
+        let mut scores = fixture_json("recent_scores.json");
+
+        for score in scores.as_array_mut().unwrap() {
+            score["ended_at"] = minutes_ago(5).into();
+        }
+
+        serde_json::from_str(&scores.to_string()).unwrap()
+    }
+
+    #[test]
+    fn parses_filters() {
+        let (filter, rest) =
+            RecentFilter::parse(tokenize("some +hdhr user -p stars=5.5 map=Blue map=zenith"))
+                .unwrap();
+
+        assert_eq!(
+            filter,
+            RecentFilter {
+                passes_only: true,
+                mods: Some(GameMods::Hidden | GameMods::HardRock),
+                search: vec!["blue".to_owned(), "zenith".to_owned()],
+                min_stars: Some(5.5),
+            }
+        );
+        assert_eq!(rest, ["some", "user"]);
+
+        let (filter, rest) = RecentFilter::parse(tokenize("\"-p\" +NM")).unwrap();
+        assert_eq!(filter.mods, Some(GameMods::NoMod));
+        assert!(!filter.passes_only);
+        assert_eq!(rest, ["-p"]);
+
+        for input in ["+XY", "stars=many", "map="] {
+            assert!(RecentFilter::parse(tokenize(input)).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn filters_scores() {
+        let scores = scores();
+        let (hidden, nomod) = (&scores[0], &scores[1]);
+
+        let filter = |input| RecentFilter::parse(tokenize(input)).unwrap().0;
+        let matches = |input, score| filter(input).matches(score, "Artist - Title [Insane]");
+
+        assert!(matches("", hidden));
+        assert!(matches("+HD", hidden));
+        assert!(!matches("+HD", nomod));
+        assert!(matches("+NM", nomod));
+        assert!(!matches("+NM", hidden));
+        assert!(matches("map=title map=insane", hidden));
+        assert!(!matches("map=extra", hidden));
+        assert!(filter("").matches_stars(5.0));
+        assert!(filter("stars=5").matches_stars(5.0));
+        assert!(!filter("stars=5.1").matches_stars(5.0));
+
+        let mut failed = hidden.clone();
+        failed.passed = false;
+        assert!(matches("", &failed));
+        assert!(!matches("--passes", &failed));
+    }
+
+    #[test]
+    fn groups_tries() {
+        let mut scores = scores();
+        scores.push(scores[0].clone());
+        scores[1].map_id = 2;
+        scores[2].score = 999_999;
+        scores[2].passed = false;
+
+        assert_eq!(group_tries(&scores), [vec![0, 2], vec![1]]);
+
+        // passes are better than fails with more score
+        assert_eq!(best_try(&scores, &[0, 2]), 0);
+
+        scores[2].passed = true;
+        assert_eq!(best_try(&scores, &[0, 2]), 2);
+    }
+}
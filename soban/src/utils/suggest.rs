@@ -0,0 +1,48 @@
+use soban_suggest::levenshtein;
+
+/// Find the candidate closest to `input` if it's close enough to likely be a typo.
+///
+/// Inputs shorter than three characters are never matched since nearly
+/// every short word would be close to some command.
+pub fn did_you_mean<'c>(
+    input: &str,
+    candidates: impl IntoIterator<Item = &'c str>,
+) -> Option<&'c str> {
+    let len = input.chars().count();
+
+    if len < 3 {
+        return None;
+    }
+
+    let max_distance = (len / 3).max(1);
+
+    candidates
+        .into_iter()
+        .map(|candidate| (candidate, levenshtein(input, candidate)))
+        .filter(|(_, distance)| (1..=max_distance).contains(distance))
+        .min_by_key(|(candidate, distance)| (*distance, *candidate))
+        .map(|(candidate, _)| candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 5] = ["recent", "rs", "osu", "ping", "p"];
+
+    #[test]
+    fn suggests_close_names() {
+        assert_eq!(did_you_mean("rcent", NAMES), Some("recent"));
+        assert_eq!(did_you_mean("recnet", NAMES), Some("recent"));
+        assert_eq!(did_you_mean("pnig", NAMES), None);
+        assert_eq!(did_you_mean("osy", NAMES), Some("osu"));
+    }
+
+    #[test]
+    fn ignores_short_and_unrelated_words() {
+        assert_eq!(did_you_mean("r", NAMES), None);
+        assert_eq!(did_you_mean("ok", NAMES), None);
+        assert_eq!(did_you_mean("hello", NAMES), None);
+        assert_eq!(did_you_mean("osu", NAMES), None);
+    }
+}